@@ -0,0 +1,98 @@
+use std::slice;
+use std::str::FromStr;
+
+/// An option given on the command line as `--name value`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Opt<'a> {
+    pub name: &'a str,
+    pub value: &'a str,
+}
+
+impl<'a> Opt<'a> {
+    /// Error for a value the option doesn't accept
+    pub fn invalid(&self) -> String {
+        format!("Invalid value for {}: {}", self.name, self.value)
+    }
+
+    /// Error for an option that isn't supported
+    pub fn unknown(&self) -> String {
+        format!("Unknown argument: {}", self.name)
+    }
+
+    pub fn parse<T: FromStr>(&self) -> Result<T, String> {
+        self.value.parse().map_err(|_| self.invalid())
+    }
+}
+
+/// Iterator over `--name value` pairs, failing when the last option has no value
+pub struct Opts<'a> {
+    args: slice::Iter<'a, String>,
+}
+
+impl<'a> Iterator for Opts<'a> {
+    type Item = Result<Opt<'a>, String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let name = self.args.next()?;
+
+        Some(
+            self.args
+                .next()
+                .map(|value| Opt { name, value })
+                .ok_or_else(|| format!("Missing value for {}", name)),
+        )
+    }
+}
+
+/// Reads command line arguments as `--name value` pairs
+pub fn options(args: &[String]) -> Opts<'_> {
+    Opts { args: args.iter() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(s: &str) -> Vec<String> {
+        s.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn test_options() {
+        let args = args("--size 3 --name x");
+        let opts = options(&args).collect::<Result<Vec<_>, _>>().unwrap();
+
+        assert_eq!(
+            opts,
+            vec![
+                Opt {
+                    name: "--size",
+                    value: "3"
+                },
+                Opt {
+                    name: "--name",
+                    value: "x"
+                }
+            ]
+        );
+        assert_eq!(opts[0].parse::<u32>(), Ok(3));
+        assert_eq!(
+            opts[1].parse::<u32>(),
+            Err(String::from("Invalid value for --name: x"))
+        );
+        assert_eq!(opts[1].unknown(), "Unknown argument: --name");
+    }
+
+    #[test]
+    fn test_missing_value() {
+        let args = args("--size 3 --verbose");
+        let mut opts = options(&args);
+
+        assert!(opts.next().unwrap().is_ok());
+        assert_eq!(
+            opts.next(),
+            Some(Err(String::from("Missing value for --verbose")))
+        );
+        assert_eq!(opts.next(), None);
+    }
+}
@@ -0,0 +1,291 @@
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+/// Closed range of integers, `start` and `end` included
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Interval {
+    start: i64,
+    end: i64,
+}
+
+impl Interval {
+    pub fn new(start: i64, end: i64) -> Self {
+        assert!(start <= end, "Invalid interval: {}-{}", start, end);
+
+        Interval { start, end }
+    }
+
+    pub fn start(&self) -> i64 {
+        self.start
+    }
+
+    pub fn end(&self) -> i64 {
+        self.end
+    }
+
+    /// Number of values in the interval, never 0 since both bounds are included. The full `i64`
+    /// range holds 2^64 values, one more than `u64` can count.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> u128 {
+        self.end.abs_diff(self.start) as u128 + 1
+    }
+
+    pub fn contains_value(&self, v: i64) -> bool {
+        self.start <= v && v <= self.end
+    }
+
+    /// Whether `other` is entirely within this interval
+    pub fn contains(&self, other: &Interval) -> bool {
+        self.start <= other.start && other.end <= self.end
+    }
+
+    pub fn overlaps(&self, other: &Interval) -> bool {
+        self.start <= other.end && other.start <= self.end
+    }
+
+    /// Whether the intervals overlap or are right next to each other, like `1-3` and `4-6`
+    fn touches(&self, other: &Interval) -> bool {
+        self.start <= other.end.saturating_add(1) && other.start <= self.end.saturating_add(1)
+    }
+
+    pub fn intersection(&self, other: &Interval) -> Option<Interval> {
+        self.overlaps(other)
+            .then(|| Interval::new(self.start.max(other.start), self.end.min(other.end)))
+    }
+
+    /// The interval covering both, if there is no gap between them
+    pub fn union(&self, other: &Interval) -> Option<Interval> {
+        self.touches(other)
+            .then(|| Interval::new(self.start.min(other.start), self.end.max(other.end)))
+    }
+}
+
+impl Display for Interval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.start, self.end)
+    }
+}
+
+impl FromStr for Interval {
+    type Err = String;
+
+    /// Parses `start-end`, where both bounds can be negative, like `-5--2`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let sep = s
+            .char_indices()
+            .skip(1)
+            .find(|&(_, c)| c == '-')
+            .map(|(i, _)| i)
+            .ok_or_else(|| format!("Expected 'start-end', got '{}'", s))?;
+
+        let bound = |v: &str| {
+            v.trim()
+                .parse::<i64>()
+                .map_err(|_| format!("Invalid bound '{}' in '{}'", v, s))
+        };
+        let (start, end) = (bound(&s[..sep])?, bound(&s[sep + 1..])?);
+
+        if start > end {
+            return Err(format!("Interval '{}' ends before it starts", s));
+        }
+
+        Ok(Interval::new(start, end))
+    }
+}
+
+/// Union of intervals, kept as a sorted list of disjoint intervals with gaps between them
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IntervalSet {
+    intervals: Vec<Interval>,
+}
+
+impl IntervalSet {
+    pub fn new() -> Self {
+        IntervalSet::default()
+    }
+
+    pub fn insert(&mut self, interval: Interval) {
+        // Intervals before `lo` end too early to touch the new one, those from `hi` start too late
+        let lo = self
+            .intervals
+            .partition_point(|i| i.end.saturating_add(1) < interval.start);
+        let hi = self
+            .intervals
+            .partition_point(|i| i.start <= interval.end.saturating_add(1));
+
+        let merged = self.intervals[lo..hi]
+            .iter()
+            .fold(interval, |acc, i| acc.union(i).unwrap());
+
+        self.intervals.splice(lo..hi, [merged]);
+    }
+
+    pub fn intervals(&self) -> &[Interval] {
+        &self.intervals
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// Number of values covered by the set
+    pub fn len(&self) -> u128 {
+        self.intervals.iter().map(Interval::len).sum()
+    }
+
+    pub fn contains_value(&self, v: i64) -> bool {
+        let idx = self.intervals.partition_point(|i| i.end < v);
+
+        self.intervals.get(idx).is_some_and(|i| i.contains_value(v))
+    }
+
+    /// Smallest interval covering the whole set
+    pub fn hull(&self) -> Option<Interval> {
+        let (first, last) = (self.intervals.first()?, self.intervals.last()?);
+
+        Some(Interval::new(first.start, last.end))
+    }
+
+    /// Number of values of `within` that are covered by the set
+    pub fn coverage(&self, within: &Interval) -> u128 {
+        self.intervals
+            .iter()
+            .filter_map(|i| i.intersection(within))
+            .map(|i| i.len())
+            .sum()
+    }
+
+    /// Parts of `within` that aren't covered by the set
+    pub fn gaps(&self, within: &Interval) -> Vec<Interval> {
+        let mut gaps = Vec::new();
+        let mut next = Some(within.start);
+
+        for i in self.intervals.iter().filter(|i| i.overlaps(within)) {
+            if let Some(start) = next.filter(|&s| s < i.start) {
+                gaps.push(Interval::new(start, i.start - 1));
+            }
+            next = i.end.checked_add(1);
+        }
+
+        if let Some(start) = next.filter(|&s| s <= within.end) {
+            gaps.push(Interval::new(start, within.end));
+        }
+
+        gaps
+    }
+}
+
+impl FromIterator<Interval> for IntervalSet {
+    fn from_iter<I: IntoIterator<Item = Interval>>(iter: I) -> Self {
+        let mut set = IntervalSet::new();
+        for interval in iter {
+            set.insert(interval);
+        }
+
+        set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn iv(start: i64, end: i64) -> Interval {
+        Interval::new(start, end)
+    }
+
+    #[test]
+    fn test_interval() {
+        assert_eq!(iv(2, 8).len(), 7);
+        assert!(iv(2, 8).contains(&iv(3, 7)));
+        assert!(!iv(3, 7).contains(&iv(2, 8)));
+        assert!(iv(5, 7).overlaps(&iv(7, 9)));
+        assert!(!iv(2, 3).overlaps(&iv(4, 5)));
+
+        assert_eq!(iv(2, 6).intersection(&iv(4, 8)), Some(iv(4, 6)));
+        assert_eq!(iv(2, 3).intersection(&iv(4, 5)), None);
+        assert_eq!(iv(2, 3).union(&iv(4, 5)), Some(iv(2, 5)));
+        assert_eq!(iv(2, 3).union(&iv(5, 6)), None);
+        assert_eq!(
+            iv(i64::MIN, 0).union(&iv(1, i64::MAX)).unwrap().start(),
+            i64::MIN
+        );
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("2-4".parse(), Ok(iv(2, 4)));
+        assert_eq!("-5--2".parse(), Ok(iv(-5, -2)));
+        assert_eq!("-5-2".parse(), Ok(iv(-5, 2)));
+        assert_eq!(iv(-5, -2).to_string(), "-5--2");
+        assert_eq!(
+            "4-2".parse::<Interval>(),
+            Err(String::from("Interval '4-2' ends before it starts"))
+        );
+        assert_eq!(
+            "4".parse::<Interval>(),
+            Err(String::from("Expected 'start-end', got '4'"))
+        );
+        assert_eq!(
+            "a-2".parse::<Interval>(),
+            Err(String::from("Invalid bound 'a' in 'a-2'"))
+        );
+    }
+
+    #[test]
+    fn test_set_merges() {
+        let set = [
+            iv(10, 12),
+            iv(1, 3),
+            iv(4, 5),
+            iv(20, 30),
+            iv(11, 21),
+            iv(40, 40),
+        ]
+        .into_iter()
+        .collect::<IntervalSet>();
+
+        assert_eq!(set.intervals(), &[iv(1, 5), iv(10, 30), iv(40, 40)]);
+        assert_eq!(set.len(), 5 + 21 + 1);
+        assert_eq!(set.hull(), Some(iv(1, 40)));
+        assert!(set.contains_value(30));
+        assert!(!set.contains_value(31));
+        assert!(!set.contains_value(0));
+        assert!(IntervalSet::new().is_empty());
+    }
+
+    #[test]
+    fn test_set_gaps() {
+        let set = [iv(1, 5), iv(10, 30), iv(40, 40)]
+            .into_iter()
+            .collect::<IntervalSet>();
+
+        assert_eq!(
+            set.gaps(&iv(0, 50)),
+            vec![iv(0, 0), iv(6, 9), iv(31, 39), iv(41, 50)]
+        );
+        assert_eq!(set.gaps(&iv(3, 12)), vec![iv(6, 9)]);
+        assert_eq!(set.gaps(&iv(12, 20)), vec![]);
+        assert_eq!(set.gaps(&iv(32, 35)), vec![iv(32, 35)]);
+        assert_eq!(set.coverage(&iv(3, 12)), 6);
+
+        let full = [iv(i64::MIN, i64::MAX)]
+            .into_iter()
+            .collect::<IntervalSet>();
+        assert_eq!(full.gaps(&iv(i64::MIN, i64::MAX)), vec![]);
+    }
+
+    #[test]
+    fn test_full_range_len() {
+        let full = iv(i64::MIN, i64::MAX);
+        assert_eq!(full.len(), 1 << 64);
+
+        let set = [iv(i64::MIN, -1), iv(0, i64::MAX)]
+            .into_iter()
+            .collect::<IntervalSet>();
+        assert_eq!(set.intervals(), &[full]);
+        assert_eq!(set.len(), 1 << 64);
+        assert_eq!(set.coverage(&full), 1 << 64);
+        assert_eq!(set.coverage(&iv(-5, 5)), 11);
+    }
+}
@@ -4,6 +4,9 @@ use std::io::{BufRead, BufReader};
 use std::ops::{Add, Mul, Sub};
 use std::str::FromStr;
 
+pub mod cli;
+pub mod interval;
+
 pub fn get_input(filename: &str) -> Vec<String> {
     let file = match File::open(format!("input/{}", filename)) {
         Ok(file) => file,
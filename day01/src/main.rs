@@ -60,7 +60,7 @@ mod tests {
 9000
 
 10000";
-        let res = get_top_calories(&input, 1);
+        let res = get_top_calories(input, 1);
 
         assert_eq!(res, 24000);
     }
@@ -81,7 +81,7 @@ mod tests {
 9000
 
 10000";
-        let res = get_top_calories(&input, 3);
+        let res = get_top_calories(input, 3);
 
         assert_eq!(res, 45000);
     }
@@ -0,0 +1,191 @@
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use itertools::Itertools;
+
+use crate::rules::{Outcome, Round, Rules};
+
+/// How a round of the guide went, and how it could have gone
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RoundReport {
+    pub round: Round,
+    pub outcome: Outcome,
+    pub score: u32,
+    /// Round with the highest scoring shape against the same opponent
+    pub best: Round,
+    pub best_score: u32,
+}
+
+/// Grades a strategy guide round by round, against the best possible guide for the same opponent
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Analysis {
+    pub rounds: Vec<RoundReport>,
+}
+
+impl Analysis {
+    pub fn new(rules: &Rules, rounds: &[Round]) -> Self {
+        let rounds = rounds
+            .iter()
+            .map(|&round| {
+                let best = Round {
+                    opponent: round.opponent,
+                    player: rules.best_response(round.opponent),
+                };
+
+                RoundReport {
+                    round,
+                    outcome: rules.outcome(round.opponent, round.player),
+                    score: rules.score(&round),
+                    best,
+                    best_score: rules.score(&best),
+                }
+            })
+            .collect();
+
+        Analysis { rounds }
+    }
+
+    pub fn count(&self, outcome: Outcome) -> usize {
+        self.rounds.iter().filter(|r| r.outcome == outcome).count()
+    }
+
+    /// Number of rounds for each score
+    pub fn score_distribution(&self) -> BTreeMap<u32, usize> {
+        self.rounds
+            .iter()
+            .map(|r| r.score)
+            .counts()
+            .into_iter()
+            .collect()
+    }
+
+    pub fn total(&self) -> u32 {
+        self.rounds.iter().map(|r| r.score).sum()
+    }
+
+    pub fn optimal_total(&self) -> u32 {
+        self.rounds.iter().map(|r| r.best_score).sum()
+    }
+
+    /// Guide playing the highest scoring shape in every round
+    pub fn optimal_guide(&self) -> Vec<Round> {
+        self.rounds.iter().map(|r| r.best).collect()
+    }
+
+    pub fn report(&self, rules: &Rules) -> String {
+        let mut out = String::new();
+        let name = |id| &rules.shape(id).name;
+
+        for (idx, r) in self.rounds.iter().enumerate() {
+            writeln!(
+                out,
+                "Round {}: {} against {}, {}, score {} (best: {}, score {})",
+                idx + 1,
+                name(r.round.player),
+                name(r.round.opponent),
+                r.outcome,
+                r.score,
+                name(r.best.player),
+                r.best_score
+            )
+            .unwrap();
+        }
+
+        writeln!(
+            out,
+            "Wins: {}, draws: {}, losses: {}",
+            self.count(Outcome::Win),
+            self.count(Outcome::Draw),
+            self.count(Outcome::Loss)
+        )
+        .unwrap();
+
+        let distribution = self
+            .score_distribution()
+            .iter()
+            .map(|(score, n)| format!("{} (x{})", score, n))
+            .join(", ");
+        writeln!(out, "Scores: {}", distribution).unwrap();
+
+        writeln!(
+            out,
+            "Total: {}, optimal: {} (+{})",
+            self.total(),
+            self.optimal_total(),
+            self.optimal_total() - self.total()
+        )
+        .unwrap();
+
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::Column;
+
+    fn analyze_example(rules: &Rules, column: Column) -> Analysis {
+        let input = ["A Y", "B X", "C Z"].map(String::from);
+
+        Analysis::new(rules, &rules.parse_guide(&input, column).unwrap())
+    }
+
+    #[test]
+    fn test_analysis() {
+        let rules = Rules::rock_paper_scissors();
+        let analysis = analyze_example(&rules, Column::Move);
+
+        assert_eq!(
+            analysis
+                .rounds
+                .iter()
+                .map(|r| r.outcome)
+                .collect::<Vec<_>>(),
+            vec![Outcome::Win, Outcome::Loss, Outcome::Draw]
+        );
+        assert_eq!(analysis.count(Outcome::Win), 1);
+        assert_eq!(
+            analysis.score_distribution(),
+            BTreeMap::from([(1, 1), (6, 1), (8, 1)])
+        );
+        assert_eq!(analysis.total(), 15);
+        assert_eq!(analysis.optimal_total(), 24);
+
+        let guide = |analysis: &Analysis, column| {
+            analysis
+                .optimal_guide()
+                .iter()
+                .map(|r| rules.guide_line(r, column))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(guide(&analysis, Column::Move), vec!["A Y", "B Z", "C X"]);
+
+        // The outcome reading of the same guide doesn't change the optimal one, only how it is
+        // written
+        let fixed = analyze_example(&rules, Column::Outcome);
+        assert_eq!(fixed.total(), 12);
+        assert_eq!(fixed.optimal_guide(), analysis.optimal_guide());
+        assert_eq!(guide(&fixed, Column::Outcome), vec!["A Z", "B Z", "C Z"]);
+
+        let lines = guide(&fixed, Column::Outcome);
+        let reread = Analysis::new(&rules, &rules.parse_guide(&lines, Column::Outcome).unwrap());
+        assert_eq!(reread.total(), fixed.optimal_total());
+    }
+
+    #[test]
+    fn test_report() {
+        let rules = Rules::rock_paper_scissors();
+
+        assert_eq!(
+            analyze_example(&rules, Column::Move).report(&rules),
+            "Round 1: Paper against Rock, win, score 8 (best: Paper, score 8)
+Round 2: Rock against Paper, loss, score 1 (best: Scissors, score 9)
+Round 3: Scissors against Scissors, draw, score 6 (best: Rock, score 7)
+Wins: 1, draws: 1, losses: 1
+Scores: 1 (x1), 6 (x1), 8 (x1)
+Total: 15, optimal: 24 (+9)
+"
+        );
+    }
+}
@@ -1,105 +1,122 @@
+use std::env;
 use std::fmt::Display;
+use std::fs;
 use std::time::Instant;
 
+use aoc_common::cli::options;
 use aoc_common::get_input;
 
-fn solve(input: &[String]) -> (impl Display, impl Display) {
-    let p1 = get_score(input);
-    let p2 = get_score_fixed(input);
+use crate::analysis::Analysis;
+use crate::rules::{Column, Rules};
 
-    (p1, p2)
-}
+mod analysis;
+mod rules;
 
-#[derive(Clone, PartialEq, Eq)]
-enum Move {
-    Rock,
-    Paper,
-    Scissors,
-}
+fn solve(rules: &Rules, input: &[String]) -> (impl Display, impl Display) {
+    let p1 = get_score(rules, input, Column::Move);
+    let p2 = get_score(rules, input, Column::Outcome);
 
-fn get_score(input: &[String]) -> u32 {
-    let mut score = 0;
+    (p1, p2)
+}
 
-    for round in input {
-        let opponent = get_move(round.chars().next().unwrap());
-        let player = get_move(round.chars().nth(2).unwrap());
-        score += get_move_score(&player) + get_round_score(&opponent, &player);
-    }
+fn get_score(rules: &Rules, input: &[String], column: Column) -> u32 {
+    let rounds = rules
+        .parse_guide(input, column)
+        .unwrap_or_else(|e| panic!("{}", e));
 
-    score
+    rules.total_score(&rounds)
 }
 
-fn get_score_fixed(input: &[String]) -> u32 {
-    let mut score = 0;
+fn main() {
+    let input = get_input("day02.txt");
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    let opts = Options::from_args(&args).unwrap_or_else(|e| panic!("{}", e));
 
-    for round in input {
-        let opponent = get_move(round.chars().next().unwrap());
-        let player = determine_move(&opponent, round.chars().nth(2).unwrap());
-        score += get_move_score(&player) + get_round_score(&opponent, &player);
-    }
+    let start = Instant::now();
 
-    score
-}
+    let (r1, r2) = solve(&opts.rules, input.as_slice());
 
-fn get_move(code: char) -> Move {
-    match code {
-        'A' | 'X' => Move::Rock,
-        'B' | 'Y' => Move::Paper,
-        'C' | 'Z' => Move::Scissors,
-        _ => panic!("Invalid move"),
-    }
-}
+    let t = start.elapsed().as_nanos() as f64 / 1000.0;
 
-fn determine_move(opponent: &Move, outcome: char) -> Move {
-    match outcome {
-        'X' => match opponent {
-            Move::Rock => Move::Scissors,
-            Move::Paper => Move::Rock,
-            Move::Scissors => Move::Paper,
-        },
-        'Y' => opponent.clone(),
-        'Z' => match opponent {
-            Move::Rock => Move::Paper,
-            Move::Paper => Move::Scissors,
-            Move::Scissors => Move::Rock,
-        },
-        _ => panic!("Invalid outcome"),
-    }
-}
+    println!("Part 1: {}", r1);
+    println!("Part 2: {}", r2);
+    println!("Duration: {:.3}μs", t);
 
-fn get_move_score(move_: &Move) -> u32 {
-    match move_ {
-        Move::Rock => 1,
-        Move::Paper => 2,
-        Move::Scissors => 3,
+    if let Some(column) = opts.analyze {
+        analyze(&opts.rules, &input, column, opts.optimal_guide.as_deref())
+            .unwrap_or_else(|e| panic!("{}", e));
     }
 }
 
-fn get_round_score(opponent: &Move, player: &Move) -> u32 {
-    if opponent == player {
-        return 3;
+/// Prints how the guide does when read with `column`, optionally writing the optimal guide to a
+/// file, to be read with `column` as well
+fn analyze(
+    rules: &Rules,
+    input: &[String],
+    column: Column,
+    optimal_guide: Option<&str>,
+) -> Result<(), String> {
+    let analysis = Analysis::new(rules, &rules.parse_guide(input, column)?);
+    print!("{}", analysis.report(rules));
+
+    if let Some(path) = optimal_guide {
+        let guide = analysis
+            .optimal_guide()
+            .iter()
+            .map(|r| rules.guide_line(r, column) + "\n")
+            .collect::<String>();
+
+        fs::write(path, guide).map_err(|e| format!("Unable to write {}: {}", path, e))?;
+        println!("Wrote {}", path);
     }
 
-    match (opponent, player) {
-        (Move::Rock, Move::Paper) => 6,
-        (Move::Paper, Move::Scissors) => 6,
-        (Move::Scissors, Move::Rock) => 6,
-        _ => 0,
-    }
+    Ok(())
 }
 
-fn main() {
-    let input = get_input("day02.txt");
-
-    let start = Instant::now();
-
-    let (r1, r2) = solve(input.as_slice());
-
-    let t = start.elapsed().as_nanos() as f64 / 1000.0;
+struct Options {
+    rules: Rules,
+    analyze: Option<Column>,
+    optimal_guide: Option<String>,
+}
 
-    println!("Part 1: {}", r1);
-    println!("Part 2: {}", r2);
-    println!("Duration: {:.3}μs", t);
+impl Options {
+    /// Parses `[--game rps|rpsls] [--analyze move|outcome [--optimal-guide FILE]]`
+    fn from_args(args: &[String]) -> Result<Self, String> {
+        let mut opts = Options {
+            rules: Rules::rock_paper_scissors(),
+            analyze: None,
+            optimal_guide: None,
+        };
+
+        for opt in options(args) {
+            let opt = opt?;
+
+            match opt.name {
+                "--game" => {
+                    opts.rules = match opt.value {
+                        "rps" => Rules::rock_paper_scissors(),
+                        "rpsls" => Rules::rock_paper_scissors_lizard_spock(),
+                        _ => return Err(opt.invalid()),
+                    }
+                }
+                "--analyze" => {
+                    opts.analyze = match opt.value {
+                        "move" => Some(Column::Move),
+                        "outcome" => Some(Column::Outcome),
+                        _ => return Err(opt.invalid()),
+                    }
+                }
+                "--optimal-guide" => opts.optimal_guide = Some(opt.value.to_string()),
+                _ => return Err(opt.unknown()),
+            }
+        }
+
+        if opts.optimal_guide.is_some() && opts.analyze.is_none() {
+            return Err(String::from("--optimal-guide needs --analyze"));
+        }
+
+        Ok(opts)
+    }
 }
 
 #[cfg(test)]
@@ -119,7 +136,7 @@ C Z
         .map(String::from)
         .collect_vec();
 
-        let res = get_score(&input);
+        let res = get_score(&Rules::rock_paper_scissors(), &input, Column::Move);
 
         assert_eq!(res, 15);
     }
@@ -135,7 +152,7 @@ C Z
         .map(String::from)
         .collect_vec();
 
-        let res = get_score_fixed(&input);
+        let res = get_score(&Rules::rock_paper_scissors(), &input, Column::Outcome);
 
         assert_eq!(res, 12);
     }
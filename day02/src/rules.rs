@@ -0,0 +1,469 @@
+use itertools::Itertools;
+use std::fmt::{self, Display};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Outcome {
+    Loss,
+    Draw,
+    Win,
+}
+
+impl Outcome {
+    pub fn score(self) -> u32 {
+        match self {
+            Outcome::Loss => 0,
+            Outcome::Draw => 3,
+            Outcome::Win => 6,
+        }
+    }
+
+    /// Code of the outcome in the second column of the guide, read with `Column::Outcome`
+    fn code(self) -> char {
+        match self {
+            Outcome::Loss => 'X',
+            Outcome::Draw => 'Y',
+            Outcome::Win => 'Z',
+        }
+    }
+
+    fn from_code(code: char) -> Option<Self> {
+        match code {
+            'X' => Some(Outcome::Loss),
+            'Y' => Some(Outcome::Draw),
+            'Z' => Some(Outcome::Win),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Outcome::Loss => "loss",
+            Outcome::Draw => "draw",
+            Outcome::Win => "win",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+/// How the second column of the strategy guide is read
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    /// The shape to play
+    Move,
+    /// How the round should end, `X` to lose, `Y` to draw and `Z` to win
+    Outcome,
+}
+
+/// A shape of the game, with the codes used for it in each column of the guide
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Shape {
+    pub name: String,
+    pub score: u32,
+    pub opponent_code: char,
+    pub player_code: char,
+}
+
+impl Shape {
+    pub fn new(name: &str, score: u32, opponent_code: char, player_code: char) -> Self {
+        Shape {
+            name: String::from(name),
+            score,
+            opponent_code,
+            player_code,
+        }
+    }
+}
+
+/// Index of a shape in the rules
+pub type ShapeId = usize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Round {
+    pub opponent: ShapeId,
+    pub player: ShapeId,
+}
+
+/// Shapes of the game and which ones beat which. Shapes where neither beats the other draw.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rules {
+    shapes: Vec<Shape>,
+    /// `beats[a][b]` is whether `a` wins against `b`
+    beats: Vec<Vec<bool>>,
+    /// `responses[opponent][outcome]` is the result of `response`, looked up for every guide line
+    responses: Vec<[Option<ShapeId>; 3]>,
+}
+
+impl Rules {
+    /// Builds the rules from the shapes and `(winner, loser)` pairs of shape names
+    pub fn new(shapes: Vec<Shape>, beats: &[(&str, &str)]) -> Result<Self, String> {
+        for (i, a) in shapes.iter().enumerate() {
+            for b in &shapes[..i] {
+                if a.name == b.name {
+                    return Err(format!("Shape {} is defined twice", a.name));
+                }
+                if a.opponent_code == b.opponent_code || a.player_code == b.player_code {
+                    return Err(format!("Shapes {} and {} share a code", b.name, a.name));
+                }
+            }
+        }
+
+        let id = |name: &str| {
+            shapes
+                .iter()
+                .position(|s| s.name == name)
+                .ok_or_else(|| format!("Unknown shape {}", name))
+        };
+
+        let mut table = vec![vec![false; shapes.len()]; shapes.len()];
+        for &(winner, loser) in beats {
+            let (w, l) = (id(winner)?, id(loser)?);
+
+            if w == l {
+                return Err(format!("{} can't beat itself", winner));
+            }
+            if table[l][w] {
+                return Err(format!("{} and {} both beat each other", winner, loser));
+            }
+
+            table[w][l] = true;
+        }
+
+        let mut rules = Rules {
+            shapes,
+            beats: table,
+            responses: Vec::new(),
+        };
+        rules.responses = (0..rules.shapes.len())
+            .map(|opponent| {
+                [Outcome::Loss, Outcome::Draw, Outcome::Win]
+                    .map(|outcome| rules.find_response(opponent, outcome))
+            })
+            .collect();
+
+        Ok(rules)
+    }
+
+    pub fn rock_paper_scissors() -> Self {
+        let shapes = vec![
+            Shape::new("Rock", 1, 'A', 'X'),
+            Shape::new("Paper", 2, 'B', 'Y'),
+            Shape::new("Scissors", 3, 'C', 'Z'),
+        ];
+        let beats = [
+            ("Rock", "Scissors"),
+            ("Paper", "Rock"),
+            ("Scissors", "Paper"),
+        ];
+
+        Rules::new(shapes, &beats).unwrap()
+    }
+
+    /// Player codes go on backwards from `X`, so regular guides keep their meaning
+    pub fn rock_paper_scissors_lizard_spock() -> Self {
+        let shapes = vec![
+            Shape::new("Rock", 1, 'A', 'X'),
+            Shape::new("Paper", 2, 'B', 'Y'),
+            Shape::new("Scissors", 3, 'C', 'Z'),
+            Shape::new("Lizard", 4, 'D', 'W'),
+            Shape::new("Spock", 5, 'E', 'V'),
+        ];
+        let beats = [
+            ("Scissors", "Paper"),
+            ("Paper", "Rock"),
+            ("Rock", "Lizard"),
+            ("Lizard", "Spock"),
+            ("Spock", "Scissors"),
+            ("Scissors", "Lizard"),
+            ("Lizard", "Paper"),
+            ("Paper", "Spock"),
+            ("Spock", "Rock"),
+            ("Rock", "Scissors"),
+        ];
+
+        Rules::new(shapes, &beats).unwrap()
+    }
+
+    pub fn shape(&self, id: ShapeId) -> &Shape {
+        &self.shapes[id]
+    }
+
+    pub fn outcome(&self, opponent: ShapeId, player: ShapeId) -> Outcome {
+        if self.beats[player][opponent] {
+            Outcome::Win
+        } else if self.beats[opponent][player] {
+            Outcome::Loss
+        } else {
+            Outcome::Draw
+        }
+    }
+
+    pub fn score(&self, round: &Round) -> u32 {
+        self.shapes[round.player].score + self.outcome(round.opponent, round.player).score()
+    }
+
+    pub fn total_score(&self, rounds: &[Round]) -> u32 {
+        rounds.iter().map(|r| self.score(r)).sum()
+    }
+
+    /// Shape to play to get `outcome` against `opponent`, the highest scoring one if there are
+    /// several
+    pub fn response(&self, opponent: ShapeId, outcome: Outcome) -> Option<ShapeId> {
+        self.responses[opponent][outcome as usize]
+    }
+
+    fn find_response(&self, opponent: ShapeId, outcome: Outcome) -> Option<ShapeId> {
+        (0..self.shapes.len())
+            .filter(|&p| self.outcome(opponent, p) == outcome)
+            .max_by_key(|&p| self.shapes[p].score)
+    }
+
+    /// Highest scoring shape to play against `opponent`
+    pub fn best_response(&self, opponent: ShapeId) -> ShapeId {
+        (0..self.shapes.len())
+            .max_by_key(|&player| self.score(&Round { opponent, player }))
+            .unwrap()
+    }
+
+    /// Line of a strategy guide for the round when its second column is read as `column`, the
+    /// reverse of `parse_round`
+    pub fn guide_line(&self, round: &Round, column: Column) -> String {
+        let code = match column {
+            Column::Move => self.shapes[round.player].player_code,
+            Column::Outcome => self.outcome(round.opponent, round.player).code(),
+        };
+
+        format!("{} {}", self.shapes[round.opponent].opponent_code, code)
+    }
+
+    /// Reads a line of the strategy guide, like `A Y`
+    pub fn parse_round(&self, line: &str, column: Column) -> Result<Round, String> {
+        let (opponent, player) = line
+            .split_whitespace()
+            .map(|code| code.chars().exactly_one().ok())
+            .collect_tuple()
+            .and_then(|(o, p)| o.zip(p))
+            .ok_or_else(|| format!("Expected '<opponent> <player>', got '{}'", line))?;
+
+        let opponent = self
+            .shapes
+            .iter()
+            .position(|s| s.opponent_code == opponent)
+            .ok_or_else(|| format!("Unknown opponent move '{}'", opponent))?;
+
+        let player = match column {
+            Column::Move => self
+                .shapes
+                .iter()
+                .position(|s| s.player_code == player)
+                .ok_or_else(|| format!("Unknown player move '{}'", player))?,
+            Column::Outcome => {
+                let outcome = Outcome::from_code(player)
+                    .ok_or_else(|| format!("Unknown outcome '{}'", player))?;
+
+                self.response(opponent, outcome).ok_or_else(|| {
+                    format!(
+                        "No move gives a {} against {}",
+                        outcome, self.shapes[opponent].name
+                    )
+                })?
+            }
+        };
+
+        Ok(Round { opponent, player })
+    }
+
+    /// Parses a guide, skipping empty lines
+    pub fn parse_guide(&self, input: &[String], column: Column) -> Result<Vec<Round>, String> {
+        input
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| !line.is_empty())
+            .map(|(idx, line)| {
+                self.parse_round(line, column)
+                    .map_err(|e| format!("Line {}: {}", idx + 1, e))
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(input: &[&str]) -> Vec<String> {
+        input.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_rock_paper_scissors() {
+        let rules = Rules::rock_paper_scissors();
+
+        assert_eq!(rules.outcome(0, 1), Outcome::Win);
+        assert_eq!(rules.outcome(1, 0), Outcome::Loss);
+        assert_eq!(rules.outcome(2, 2), Outcome::Draw);
+        assert_eq!(rules.response(2, Outcome::Win), Some(0));
+        assert_eq!(rules.response(2, Outcome::Loss), Some(1));
+        assert_eq!(
+            rules.parse_round("A Y", Column::Move),
+            Ok(Round {
+                opponent: 0,
+                player: 1
+            })
+        );
+        assert_eq!(
+            rules.parse_round("A Y", Column::Outcome),
+            Ok(Round {
+                opponent: 0,
+                player: 0
+            })
+        );
+    }
+
+    #[test]
+    fn test_best_response() {
+        let rules = Rules::rock_paper_scissors();
+
+        // Winning is always worth more than the difference between shape scores
+        assert_eq!(rules.best_response(0), 1);
+        assert_eq!(rules.best_response(2), 0);
+
+        let round = Round {
+            opponent: 2,
+            player: 0,
+        };
+        assert_eq!(rules.guide_line(&round, Column::Move), "C X");
+        assert_eq!(rules.parse_round("C X", Column::Move), Ok(round));
+        assert_eq!(rules.guide_line(&round, Column::Outcome), "C Z");
+        assert_eq!(rules.parse_round("C Z", Column::Outcome), Ok(round));
+    }
+
+    #[test]
+    fn test_rock_paper_scissors_lizard_spock() {
+        let rules = Rules::rock_paper_scissors_lizard_spock();
+
+        // Every shape beats exactly two others
+        for a in 0..5 {
+            let wins = (0..5).filter(|&b| rules.outcome(b, a) == Outcome::Win);
+            assert_eq!(wins.count(), 2);
+        }
+
+        // Against Spock, Paper and Lizard win, Lizard scores more
+        assert_eq!(rules.response(4, Outcome::Win), Some(3));
+
+        // Lizard beats Spock, Spock loses to Lizard, Scissors lose to Rock
+        let guide = lines(&["E W", "D V", "A Z"]);
+        let rounds = rules.parse_guide(&guide, Column::Move).unwrap();
+        assert_eq!(rules.total_score(&rounds), 10 + 5 + 3);
+
+        // Winning against Spock with Lizard, losing to Lizard with Spock, drawing with Rock
+        let guide = lines(&["E Z", "D X", "A Y"]);
+        let rounds = rules.parse_guide(&guide, Column::Outcome).unwrap();
+        assert_eq!(rules.total_score(&rounds), 10 + 5 + 4);
+    }
+
+    #[test]
+    fn test_partial_rules() {
+        let shapes = vec![
+            Shape::new("Big", 1, 'A', 'X'),
+            Shape::new("Small", 2, 'B', 'Y'),
+        ];
+        let rules = Rules::new(shapes, &[("Big", "Small")]).unwrap();
+
+        assert_eq!(
+            rules.parse_round("A Z", Column::Outcome),
+            Err(String::from("No move gives a win against Big"))
+        );
+    }
+
+    #[test]
+    fn test_invalid_rules() {
+        let shapes = || {
+            vec![
+                Shape::new("Big", 1, 'A', 'X'),
+                Shape::new("Small", 2, 'B', 'Y'),
+            ]
+        };
+
+        assert_eq!(
+            Rules::new(shapes(), &[("Big", "Tiny")]),
+            Err(String::from("Unknown shape Tiny"))
+        );
+        assert_eq!(
+            Rules::new(shapes(), &[("Big", "Big")]),
+            Err(String::from("Big can't beat itself"))
+        );
+        assert_eq!(
+            Rules::new(shapes(), &[("Big", "Small"), ("Small", "Big")]),
+            Err(String::from("Small and Big both beat each other"))
+        );
+        assert_eq!(
+            Rules::new(
+                vec![
+                    Shape::new("Big", 1, 'A', 'X'),
+                    Shape::new("Small", 2, 'A', 'Y')
+                ],
+                &[]
+            ),
+            Err(String::from("Shapes Big and Small share a code"))
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let rules = Rules::rock_paper_scissors();
+        let parse = |input: &[&str], column| rules.parse_guide(&lines(input), column);
+
+        assert_eq!(
+            parse(&["A Y", "B"], Column::Move),
+            Err(String::from(
+                "Line 2: Expected '<opponent> <player>', got 'B'"
+            ))
+        );
+        assert_eq!(
+            parse(&["A Y", "B X", "AB Y"], Column::Move),
+            Err(String::from(
+                "Line 3: Expected '<opponent> <player>', got 'AB Y'"
+            ))
+        );
+        assert_eq!(
+            parse(&["  ", "B X"], Column::Move),
+            Err(String::from(
+                "Line 1: Expected '<opponent> <player>', got '  '"
+            ))
+        );
+        assert_eq!(
+            parse(&["A Y", "", "B"], Column::Move),
+            Err(String::from(
+                "Line 3: Expected '<opponent> <player>', got 'B'"
+            ))
+        );
+        assert_eq!(
+            parse(&["", "C\tX", "", " B  Z ", ""], Column::Move),
+            Ok(vec![
+                Round {
+                    opponent: 2,
+                    player: 0
+                },
+                Round {
+                    opponent: 1,
+                    player: 2
+                }
+            ])
+        );
+        assert_eq!(
+            parse(&["D Y"], Column::Move),
+            Err(String::from("Line 1: Unknown opponent move 'D'"))
+        );
+        assert_eq!(
+            parse(&["A W"], Column::Move),
+            Err(String::from("Line 1: Unknown player move 'W'"))
+        );
+        assert_eq!(
+            parse(&["A W"], Column::Outcome),
+            Err(String::from("Line 1: Unknown outcome 'W'"))
+        );
+    }
+}
@@ -1,14 +1,14 @@
 use itertools::Itertools;
+use std::env;
 use std::fmt::Display;
 use std::time::Instant;
 
 use aoc_common::get_input;
+use aoc_common::interval::{Interval, IntervalSet};
 
-fn solve(input: &[String]) -> (impl Display, impl Display) {
-    let pairs = parse_pairs(input);
-
-    let p1 = count_contained_pairs(&pairs);
-    let p2 = count_overlapping_pairs(&pairs);
+fn solve(pairs: &[(Interval, Interval)]) -> (impl Display, impl Display) {
+    let p1 = count_contained_pairs(pairs);
+    let p2 = count_overlapping_pairs(pairs);
 
     (p1, p2)
 }
@@ -18,70 +18,66 @@ fn main() {
 
     let start = Instant::now();
 
-    let (r1, r2) = solve(input.as_slice());
+    let pairs = parse_pairs(&input);
+
+    let (r1, r2) = solve(&pairs);
 
     let t = start.elapsed().as_nanos() as f64 / 1000.0;
 
     println!("Part 1: {}", r1);
     println!("Part 2: {}", r2);
     println!("Duration: {:.3}μs", t);
-}
-
-struct Pair {
-    x: u32,
-    y: u32,
-}
-
-impl Pair {
-    #[cfg(test)]
-    fn new(x: u32, y: u32) -> Self {
-        Pair { x, y }
-    }
-
-    fn contains(&self, other: &Pair) -> bool {
-        self.x <= other.x && self.y >= other.y
-    }
-
-    fn overlaps(&self, other: &Pair) -> bool {
-        self.x <= other.y && self.y >= other.x
-    }
-}
 
-impl From<&str> for Pair {
-    fn from(s: &str) -> Self {
-        let (x, y) = s
-            .split('-')
-            .map(|v| v.parse().unwrap())
-            .collect_tuple::<(u32, u32)>()
-            .unwrap();
+    if env::args().nth(1).as_deref() == Some("--gaps") {
+        let gaps = uncovered_sections(&pairs);
+        let total = gaps.iter().map(Interval::len).sum::<u128>();
 
-        Pair { x, y }
+        println!("Sections covered by nobody: {}", total);
+        for gap in gaps {
+            println!("  {}", gap);
+        }
     }
 }
 
-fn parse_pairs(input: &[String]) -> Vec<(Pair, Pair)> {
+fn parse_pairs(input: &[String]) -> Vec<(Interval, Interval)> {
     input
         .iter()
-        .map(|s| {
-            s.split(',')
-                .map(|v| v.into())
-                .collect_tuple::<(Pair, Pair)>()
-                .unwrap()
-        })
+        .enumerate()
+        .map(|(idx, s)| parse_pair(s).unwrap_or_else(|e| panic!("Line {}: {}", idx + 1, e)))
         .collect_vec()
 }
 
-fn count_contained_pairs(pairs: &[(Pair, Pair)]) -> usize {
+fn parse_pair(s: &str) -> Result<(Interval, Interval), String> {
+    let (a, b) = s
+        .split_once(',')
+        .ok_or_else(|| format!("Expected two assignments, got '{}'", s))?;
+
+    Ok((a.parse()?, b.parse()?))
+}
+
+fn count_contained_pairs(pairs: &[(Interval, Interval)]) -> usize {
     pairs
         .iter()
         .filter(|(p1, p2)| p1.contains(p2) || p2.contains(p1))
         .count()
 }
 
-fn count_overlapping_pairs(pairs: &[(Pair, Pair)]) -> usize {
+fn count_overlapping_pairs(pairs: &[(Interval, Interval)]) -> usize {
     pairs.iter().filter(|(p1, p2)| p1.overlaps(p2)).count()
 }
 
+/// Sections between the lowest and highest assigned ones that no elf has to clean
+fn uncovered_sections(pairs: &[(Interval, Interval)]) -> Vec<Interval> {
+    let covered = pairs
+        .iter()
+        .flat_map(|&(p1, p2)| [p1, p2])
+        .collect::<IntervalSet>();
+
+    covered
+        .hull()
+        .map_or(Vec::new(), |hull| covered.gaps(&hull))
+}
+
 #[cfg(test)]
 mod tests {
     use itertools::Itertools;
@@ -131,11 +127,44 @@ mod tests {
 
     #[test]
     fn test_pair_overlaps() {
-        assert!(!Pair::new(2, 4).overlaps(&Pair::new(6, 8)));
-        assert!(!Pair::new(2, 3).overlaps(&Pair::new(4, 5)));
-        assert!(Pair::new(5, 7).overlaps(&Pair::new(7, 9)));
-        assert!(Pair::new(2, 8).overlaps(&Pair::new(3, 7)));
-        assert!(Pair::new(6, 6).overlaps(&Pair::new(4, 6)));
-        assert!(Pair::new(2, 6).overlaps(&Pair::new(4, 8)));
+        let pair = |a, b| Interval::new(a, b);
+
+        assert!(!pair(2, 4).overlaps(&pair(6, 8)));
+        assert!(!pair(2, 3).overlaps(&pair(4, 5)));
+        assert!(pair(5, 7).overlaps(&pair(7, 9)));
+        assert!(pair(2, 8).overlaps(&pair(3, 7)));
+        assert!(pair(6, 6).overlaps(&pair(4, 6)));
+        assert!(pair(2, 6).overlaps(&pair(4, 8)));
+    }
+
+    #[test]
+    fn test_uncovered_sections() {
+        let input = ["2-4,6-8", "2-3,4-5", "12-14,10-10"].map(String::from);
+
+        let pairs = parse_pairs(&input);
+
+        assert_eq!(
+            uncovered_sections(&pairs),
+            vec![Interval::new(9, 9), Interval::new(11, 11)]
+        );
+        assert_eq!(uncovered_sections(&[]), vec![]);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse_pair("2-4"),
+            Err(String::from("Expected two assignments, got '2-4'"))
+        );
+        assert_eq!(
+            parse_pair("2-4,8-6"),
+            Err(String::from("Interval '8-6' ends before it starts"))
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Line 2: Invalid bound 'x' in 'x-3'")]
+    fn test_parse_line_number() {
+        parse_pairs(&["2-4,6-8", "x-3,4-5"].map(String::from));
     }
 }
@@ -0,0 +1,208 @@
+use std::fmt::{self, Display};
+
+/// Stacks of crates, bottom first, so the top crate of each stack is the last one
+pub type Stacks = Vec<Vec<char>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Move {
+    pub n: usize,
+    pub from: usize,
+    pub to: usize,
+}
+
+impl Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "move {} from {} to {}", self.n, self.from, self.to)
+    }
+}
+
+pub trait Crane {
+    /// Performs a single move, leaving the stacks untouched if it isn't possible
+    fn apply(&self, stacks: &mut Stacks, m: &Move) -> Result<(), String>;
+
+    /// Performs every move in order, stopping at the first one that isn't possible
+    fn run(&self, stacks: &mut Stacks, moves: &[Move]) -> Result<(), String> {
+        for (idx, m) in moves.iter().enumerate() {
+            self.apply(stacks, m)
+                .map_err(|e| format!("Move {} ({}): {}", idx + 1, m, e))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Moves crates one at a time, reversing their order
+pub struct CrateMover9000;
+
+/// Moves all the crates at once, keeping their order
+pub struct CrateMover9001;
+
+/// Lifts at most `capacity` crates at once, keeping their order within each lift
+pub struct LimitedCrane {
+    capacity: usize,
+}
+
+impl LimitedCrane {
+    pub fn new(capacity: usize) -> Self {
+        assert!(capacity > 0, "A crane must be able to lift a crate");
+
+        LimitedCrane { capacity }
+    }
+}
+
+/// Checks that a move is possible, returning the 0-based indices of its stacks
+fn check(stacks: &Stacks, m: &Move) -> Result<(usize, usize), String> {
+    for stack in [m.from, m.to] {
+        if stack == 0 || stack > stacks.len() {
+            return Err(format!(
+                "No stack {}, there are {} stacks",
+                stack,
+                stacks.len()
+            ));
+        }
+    }
+
+    let available = stacks[m.from - 1].len();
+    if available < m.n {
+        return Err(format!(
+            "Stack {} has only {} crates, {} needed",
+            m.from, available, m.n
+        ));
+    }
+
+    Ok((m.from - 1, m.to - 1))
+}
+
+/// Moves the top `n` crates of `from` on top of `to`, as a block or in reverse order. Moving
+/// crates onto their own stack leaves it as it was, whatever the order.
+fn lift(stacks: &mut Stacks, from: usize, to: usize, n: usize, reverse: bool) {
+    if from == to {
+        return;
+    }
+
+    let at = stacks[from].len() - n;
+    let crates = stacks[from].split_off(at);
+
+    if reverse {
+        stacks[to].extend(crates.into_iter().rev());
+    } else {
+        stacks[to].extend(crates);
+    }
+}
+
+impl Crane for CrateMover9000 {
+    fn apply(&self, stacks: &mut Stacks, m: &Move) -> Result<(), String> {
+        let (from, to) = check(stacks, m)?;
+
+        // Moving crates one at a time is the same as moving them all, reversed
+        lift(stacks, from, to, m.n, true);
+
+        Ok(())
+    }
+}
+
+impl Crane for CrateMover9001 {
+    fn apply(&self, stacks: &mut Stacks, m: &Move) -> Result<(), String> {
+        let (from, to) = check(stacks, m)?;
+
+        lift(stacks, from, to, m.n, false);
+
+        Ok(())
+    }
+}
+
+impl Crane for LimitedCrane {
+    fn apply(&self, stacks: &mut Stacks, m: &Move) -> Result<(), String> {
+        let (from, to) = check(stacks, m)?;
+
+        let mut left = m.n;
+        while left > 0 {
+            let n = left.min(self.capacity);
+            lift(stacks, from, to, n, false);
+            left -= n;
+        }
+
+        Ok(())
+    }
+}
+
+/// Crates at the top of each stack, skipping empty ones
+pub fn tops(stacks: &Stacks) -> String {
+    stacks.iter().filter_map(|s| s.last()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stacks() -> Stacks {
+        vec![vec!['Z', 'N'], vec!['M', 'C', 'D'], vec!['P']]
+    }
+
+    fn mv(n: usize, from: usize, to: usize) -> Move {
+        Move { n, from, to }
+    }
+
+    #[test]
+    fn test_cranes() {
+        let mut s = stacks();
+        CrateMover9000.apply(&mut s, &mv(3, 2, 1)).unwrap();
+        assert_eq!(s, vec![vec!['Z', 'N', 'D', 'C', 'M'], vec![], vec!['P']]);
+
+        let mut s = stacks();
+        CrateMover9001.apply(&mut s, &mv(3, 2, 1)).unwrap();
+        assert_eq!(s, vec![vec!['Z', 'N', 'M', 'C', 'D'], vec![], vec!['P']]);
+
+        let mut s = stacks();
+        LimitedCrane::new(2).apply(&mut s, &mv(3, 2, 1)).unwrap();
+        assert_eq!(s, vec![vec!['Z', 'N', 'C', 'D', 'M'], vec![], vec!['P']]);
+        assert_eq!(tops(&s), "MP");
+    }
+
+    #[test]
+    fn test_limited_crane_extremes() {
+        let moves = [mv(1, 2, 1), mv(3, 1, 3), mv(2, 2, 1), mv(1, 1, 2)];
+
+        for (capacity, expected) in [(1, "CMZ"), (3, "MCD"), (100, "MCD")] {
+            let mut s = stacks();
+            LimitedCrane::new(capacity).run(&mut s, &moves).unwrap();
+            assert_eq!(tops(&s), expected);
+        }
+    }
+
+    #[test]
+    fn test_move_onto_same_stack() {
+        for crane in [
+            &CrateMover9000 as &dyn Crane,
+            &CrateMover9001,
+            &LimitedCrane::new(2),
+        ] {
+            let mut s = stacks();
+            crane.apply(&mut s, &mv(3, 2, 2)).unwrap();
+            assert_eq!(s, stacks());
+        }
+    }
+
+    #[test]
+    fn test_errors() {
+        let mut s = stacks();
+
+        assert_eq!(
+            CrateMover9001.run(&mut s, &[mv(1, 2, 1), mv(3, 1, 3), mv(3, 2, 1)]),
+            Err(String::from(
+                "Move 3 (move 3 from 2 to 1): Stack 2 has only 2 crates, 3 needed"
+            ))
+        );
+        assert_eq!(s, vec![vec![], vec!['M', 'C'], vec!['P', 'Z', 'N', 'D']]);
+
+        assert_eq!(
+            CrateMover9000.apply(&mut s, &mv(1, 2, 4)),
+            Err(String::from("No stack 4, there are 3 stacks"))
+        );
+        assert_eq!(
+            CrateMover9000.apply(&mut s, &mv(1, 0, 1)),
+            Err(String::from("No stack 0, there are 3 stacks"))
+        );
+        assert_eq!(s, vec![vec![], vec!['M', 'C'], vec!['P', 'Z', 'N', 'D']]);
+    }
+}
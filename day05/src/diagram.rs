@@ -0,0 +1,207 @@
+use std::fmt::{self, Display};
+
+use crate::crane::Stacks;
+
+/// Drawing of the stacks of crates, as found at the top of the puzzle input
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagram {
+    /// Labels of the stacks, from left to right
+    pub ids: Vec<String>,
+    pub stacks: Stacks,
+}
+
+impl Diagram {
+    /// 1-based index of the stack with the given label, as used by `Move`
+    pub fn stack_index(&self, id: &str) -> Option<usize> {
+        self.ids.iter().position(|i| i == id).map(|idx| idx + 1)
+    }
+
+    /// Reads a diagram whose last line labels the stacks. Crates are assigned to the closest label,
+    /// so lines can be ragged and labels can be wider than crates.
+    pub fn parse(lines: &[&str]) -> Result<Self, String> {
+        let (labels, crate_lines) = lines
+            .split_last()
+            .ok_or_else(|| String::from("Empty diagram"))?;
+
+        // Labels with the columns they span
+        let mut spans = Vec::new();
+        let mut start = None;
+        for (col, c) in labels.chars().chain([' ']).enumerate() {
+            match (c.is_whitespace(), start) {
+                (false, None) => start = Some(col),
+                (true, Some(s)) => {
+                    spans.push((s, col));
+                    start = None;
+                }
+                _ => {}
+            }
+        }
+
+        if spans.is_empty() {
+            return Err(format!("No stack labels in '{}'", labels));
+        }
+
+        let ids = spans
+            .iter()
+            .map(|&(s, e)| labels.chars().skip(s).take(e - s).collect::<String>())
+            .collect::<Vec<_>>();
+        let mut stacks: Stacks = vec![Vec::new(); ids.len()];
+
+        // Going up from the bottom, a crate must always land on the previous one
+        for (level, (idx, line)) in crate_lines.iter().enumerate().rev().enumerate() {
+            let chars = line.chars().collect::<Vec<_>>();
+            let mut col = 0;
+
+            while col < chars.len() {
+                match chars[col..] {
+                    [' ', ..] => col += 1,
+                    ['[', c, ']', ..] if c.is_alphanumeric() => {
+                        let letter = col + 1;
+                        let distance = |&(s, e): &(usize, usize)| {
+                            if letter < s {
+                                s - letter
+                            } else {
+                                (letter + 1).saturating_sub(e)
+                            }
+                        };
+
+                        let stack = (0..spans.len())
+                            .min_by_key(|&i| distance(&spans[i]))
+                            .filter(|&i| distance(&spans[i]) <= 1)
+                            .ok_or_else(|| {
+                                format!(
+                                    "Line {}: crate {} at column {} isn't above any stack",
+                                    idx + 1,
+                                    c,
+                                    letter + 1
+                                )
+                            })?;
+
+                        if stacks[stack].len() != level {
+                            return Err(format!(
+                                "Line {}: crate {} is floating above stack {}",
+                                idx + 1,
+                                c,
+                                ids[stack]
+                            ));
+                        }
+
+                        stacks[stack].push(c);
+                        col += 3;
+                    }
+                    _ => {
+                        return Err(format!(
+                            "Line {}: unexpected '{}' at column {}",
+                            idx + 1,
+                            chars[col],
+                            col + 1
+                        ))
+                    }
+                }
+            }
+        }
+
+        Ok(Diagram { ids, stacks })
+    }
+}
+
+impl Display for Diagram {
+    /// Draws the stacks back the way the puzzle does
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let widths = self
+            .ids
+            .iter()
+            .map(|id| id.len().max(3))
+            .collect::<Vec<_>>();
+        let height = self.stacks.iter().map(Vec::len).max().unwrap_or(0);
+
+        for level in (0..height).rev() {
+            let row = self
+                .stacks
+                .iter()
+                .zip(&widths)
+                .map(|(stack, &w)| match stack.get(level) {
+                    Some(c) => format!("{:^w$}", format!("[{}]", c), w = w),
+                    None => " ".repeat(w),
+                })
+                .collect::<Vec<_>>();
+
+            writeln!(f, "{}", row.join(" "))?;
+        }
+
+        let labels = self
+            .ids
+            .iter()
+            .zip(&widths)
+            .map(|(id, &w)| format!("{:^w$}", id, w = w))
+            .collect::<Vec<_>>();
+
+        writeln!(f, "{}", labels.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIAGRAM: [&str; 4] = ["    [D]    ", "[N] [C]    ", "[Z] [M] [P]", " 1   2   3 "];
+
+    #[test]
+    fn test_round_trip() {
+        let diagram = Diagram::parse(&DIAGRAM).unwrap();
+
+        assert_eq!(diagram.ids, vec!["1", "2", "3"]);
+        assert_eq!(
+            diagram.stacks,
+            vec![vec!['Z', 'N'], vec!['M', 'C', 'D'], vec!['P']]
+        );
+        assert_eq!(diagram.to_string(), DIAGRAM.join("\n") + "\n");
+    }
+
+    #[test]
+    fn test_ragged_lines_and_wide_labels() {
+        let diagram = Diagram::parse(&[
+            "                                       [J]",
+            "[A]                                 [I] [K]",
+            " 1   2   3   4   5   6   7   8   9  10  11",
+        ])
+        .unwrap();
+
+        assert_eq!(diagram.ids.len(), 11);
+        assert_eq!(diagram.stack_index("10"), Some(10));
+        assert_eq!(diagram.stack_index("12"), None);
+        assert_eq!(diagram.stacks[0], vec!['A']);
+        assert_eq!(diagram.stacks[9], vec!['I']);
+        assert_eq!(diagram.stacks[10], vec!['K', 'J']);
+
+        // Empty stacks are still drawn
+        let diagram = Diagram {
+            ids: vec![String::from("1"), String::from("200")],
+            stacks: vec![vec![], vec!['X']],
+        };
+        assert_eq!(diagram.to_string(), "    [X]\n 1  200\n");
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            Diagram::parse(&["[A] [B]", "[C]    ", " 1   2 "]),
+            Err(String::from("Line 1: crate B is floating above stack 2"))
+        );
+        assert_eq!(
+            Diagram::parse(&["[A]     [B]", " 1   2 "]),
+            Err(String::from(
+                "Line 1: crate B at column 10 isn't above any stack"
+            ))
+        );
+        assert_eq!(
+            Diagram::parse(&["[A] {B}", " 1   2 "]),
+            Err(String::from("Line 1: unexpected '{' at column 5"))
+        );
+        assert_eq!(
+            Diagram::parse(&["[A]", "   "]),
+            Err(String::from("No stack labels in '   '"))
+        );
+        assert_eq!(Diagram::parse(&[]), Err(String::from("Empty diagram")));
+    }
+}
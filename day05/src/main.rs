@@ -1,15 +1,22 @@
 use itertools::Itertools;
-use std::collections::VecDeque;
+use std::env;
 use std::fmt::Display;
 use std::time::Instant;
 
+use aoc_common::cli::options;
 use aoc_common::get_input;
 
+use crate::crane::{tops, Crane, CrateMover9000, CrateMover9001, LimitedCrane, Move, Stacks};
+use crate::diagram::Diagram;
+
+mod crane;
+mod diagram;
+
 fn solve(input: &[String]) -> (impl Display, impl Display) {
-    let (mut towers, moves) = parse_input(&input.iter().map(|s| s.as_str()).collect_vec());
+    let (diagram, moves) = parse(input);
 
-    let p1 = process_moves(&mut towers.clone(), &moves);
-    let p2 = process_moves_9001(&mut towers, &moves);
+    let p1 = process_moves(&CrateMover9000, diagram.stacks.clone(), &moves);
+    let p2 = process_moves(&CrateMover9001, diagram.stacks, &moves);
 
     (p1, p2)
 }
@@ -26,90 +33,117 @@ fn main() {
     println!("Part 1: {}", r1);
     println!("Part 2: {}", r2);
     println!("Duration: {:.3}μs", t);
-}
-
-#[derive(Debug, PartialEq, Eq)]
-struct Move {
-    n: usize,
-    from: usize,
-    to: usize,
-}
 
-fn parse_input(input: &[&str]) -> (Vec<VecDeque<char>>, Vec<Move>) {
-    let nb_towers = input[0].len() / 4 + 1;
-    let mut towers: Vec<VecDeque<char>> = Vec::with_capacity(nb_towers);
+    let args = env::args().skip(1).collect_vec();
+    if !args.is_empty() {
+        let (diagram, moves) = parse(&input);
+        let opts = Options::from_args(&args).unwrap_or_else(|e| panic!("{}", e));
 
-    for _ in 0..nb_towers {
-        towers.push(VecDeque::new());
+        show_steps(opts.crane.as_ref(), diagram, &moves, opts.steps);
     }
+}
 
-    let mut idx: usize = 0;
-
-    loop {
-        let s = input[idx];
-        idx += 1;
+struct Options {
+    crane: Box<dyn Crane>,
+    steps: usize,
+}
 
-        for (i, tower) in towers.iter_mut().enumerate() {
-            if let Some(c) = s.chars().nth(i * 4 + 1) {
-                if c.is_alphabetic() {
-                    tower.push_back(c);
+impl Options {
+    /// Parses `[--crane 9000|9001|CAPACITY] [--steps N]`, showing the stacks after each of the
+    /// first N moves
+    fn from_args(args: &[String]) -> Result<Self, String> {
+        let mut opts = Options {
+            crane: Box::new(CrateMover9001),
+            steps: 0,
+        };
+
+        for opt in options(args) {
+            let opt = opt?;
+
+            match opt.name {
+                "--crane" => {
+                    opts.crane = match opt.value {
+                        "9000" => Box::new(CrateMover9000),
+                        "9001" => Box::new(CrateMover9001),
+                        v => match v.parse() {
+                            Ok(capacity) if capacity > 0 => Box::new(LimitedCrane::new(capacity)),
+                            _ => return Err(opt.invalid()),
+                        },
+                    }
                 }
+                "--steps" => opts.steps = opt.parse()?,
+                _ => return Err(opt.unknown()),
             }
         }
 
-        if s.is_empty() {
-            break;
-        }
+        Ok(opts)
     }
+}
 
-    let mut moves = Vec::new();
-
-    loop {
-        if idx >= input.len() {
-            break;
-        }
-
-        let (n, from, to) = input[idx]
-            .split(' ')
-            .map(|i| i.parse::<usize>().unwrap_or(0))
-            .filter(|&i| i != 0)
-            .collect_tuple()
-            .unwrap();
-
-        moves.push(Move { n, from, to });
-
-        idx += 1;
-    }
+fn show_steps(crane: &dyn Crane, mut diagram: Diagram, moves: &[Move], steps: usize) {
+    print!("{}", diagram);
 
-    (towers, moves)
-}
+    for (idx, m) in moves.iter().enumerate() {
+        crane
+            .apply(&mut diagram.stacks, m)
+            .unwrap_or_else(|e| panic!("Move {} ({}): {}", idx + 1, m, e));
 
-fn process_moves(towers: &mut [VecDeque<char>], moves: &[Move]) -> String {
-    for m in moves {
-        for _ in 0..m.n {
-            let c = towers[m.from - 1].pop_front().unwrap();
-            towers[m.to - 1].push_front(c)
+        if idx < steps {
+            println!("\n{}\n", m);
+            print!("{}", diagram);
         }
     }
 
-    towers.iter().map(|t| t[0]).collect::<String>()
+    println!("\nTop crates: {}", tops(&diagram.stacks));
 }
 
-fn process_moves_9001(towers: &mut [VecDeque<char>], moves: &[Move]) -> String {
-    for m in moves {
-        let mut tmp = VecDeque::new();
+fn parse(input: &[String]) -> (Diagram, Vec<Move>) {
+    parse_input(&input.iter().map(|s| s.as_str()).collect_vec()).unwrap_or_else(|e| panic!("{}", e))
+}
 
-        for _ in 0..m.n {
-            let c = towers[m.from - 1].pop_front().unwrap();
-            tmp.push_front(c);
-        }
+fn parse_input(input: &[&str]) -> Result<(Diagram, Vec<Move>), String> {
+    let blank = input
+        .iter()
+        .position(|s| s.trim().is_empty())
+        .unwrap_or(input.len());
+    let diagram = Diagram::parse(&input[..blank])?;
+
+    let moves = input
+        .iter()
+        .enumerate()
+        .skip(blank + 1)
+        .filter(|(_, s)| !s.is_empty())
+        .map(|(idx, s)| parse_move(s, &diagram).map_err(|e| format!("Line {}: {}", idx + 1, e)))
+        .collect::<Result<_, _>>()?;
+
+    Ok((diagram, moves))
+}
 
-        for c in tmp.iter() {
-            towers[m.to - 1].push_front(*c)
-        }
+/// Parses `move N from A to B`, where `A` and `B` are stack labels
+fn parse_move(s: &str, diagram: &Diagram) -> Result<Move, String> {
+    let stack = |id: &str| {
+        diagram
+            .stack_index(id)
+            .ok_or_else(|| format!("Unknown stack {}", id))
+    };
+
+    match s.split_whitespace().collect_vec().as_slice() {
+        ["move", n, "from", from, "to", to] => Ok(Move {
+            n: n.parse()
+                .map_err(|_| format!("Invalid number of crates: {}", n))?,
+            from: stack(from)?,
+            to: stack(to)?,
+        }),
+        _ => Err(format!("Invalid move: '{}'", s)),
     }
+}
+
+fn process_moves<C: Crane + ?Sized>(crane: &C, mut towers: Stacks, moves: &[Move]) -> String {
+    crane
+        .run(&mut towers, moves)
+        .unwrap_or_else(|e| panic!("{}", e));
 
-    towers.iter().map(|t| t[0]).collect::<String>()
+    tops(&towers)
 }
 
 #[cfg(test)]
@@ -130,10 +164,11 @@ mod tests {
             "move 1 from 1 to 2",
         ];
 
-        let (towers, moves) = parse_input(&input);
+        let (diagram, moves) = parse_input(&input).unwrap();
+        let towers = diagram.stacks;
         assert_eq!(towers.len(), 3);
-        assert_eq!(towers[0], vec!('N', 'Z'));
-        assert_eq!(towers[1], vec!('D', 'C', 'M'));
+        assert_eq!(towers[0], vec!('Z', 'N'));
+        assert_eq!(towers[1], vec!('M', 'C', 'D'));
         assert_eq!(towers[2], vec!('P'));
 
         assert_eq!(
@@ -177,9 +212,9 @@ mod tests {
             "move 1 from 1 to 2",
         ];
 
-        let (mut towers, moves) = parse_input(&input);
+        let (diagram, moves) = parse_input(&input).unwrap();
 
-        let res = process_moves(&mut towers, &moves);
+        let res = process_moves(&CrateMover9000, diagram.stacks, &moves);
 
         assert_eq!(res, "CMZ");
     }
@@ -198,10 +233,28 @@ mod tests {
             "move 1 from 1 to 2",
         ];
 
-        let (mut towers, moves) = parse_input(&input);
+        let (diagram, moves) = parse_input(&input).unwrap();
 
-        let res = process_moves_9001(&mut towers, &moves);
+        let res = process_moves(&CrateMover9001, diagram.stacks, &moves);
 
         assert_eq!(res, "MCD");
     }
+
+    #[test]
+    fn test_parse_moves_errors() {
+        let input = |m| vec!["[A] [B]", " 1   2 ", "", "move 1 from 1 to 2", m];
+
+        assert_eq!(
+            parse_input(&input("move 1 from 1 to 3")),
+            Err(String::from("Line 5: Unknown stack 3"))
+        );
+        assert_eq!(
+            parse_input(&input("move x from 1 to 2")),
+            Err(String::from("Line 5: Invalid number of crates: x"))
+        );
+        assert_eq!(
+            parse_input(&input("move 1 to 2")),
+            Err(String::from("Line 5: Invalid move: 'move 1 to 2'"))
+        );
+    }
 }
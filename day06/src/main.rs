@@ -1,12 +1,14 @@
-use std::collections::HashSet;
+use std::env;
 use std::fmt::Display;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read};
 use std::time::Instant;
 
 use aoc_common::get_input_as_string;
 
 fn solve(input: &str) -> (impl Display, impl Display) {
-    let p1 = get_first_marker(input, 4);
-    let p2 = get_first_marker(input, 14);
+    let p1 = get_first_marker(input, 4).expect("No start-of-packet marker");
+    let p2 = get_first_marker(input, 14).expect("No start-of-message marker");
 
     (p1, p2)
 }
@@ -23,46 +25,152 @@ fn main() {
     println!("Part 1: {}", r1);
     println!("Part 2: {}", r2);
     println!("Duration: {:.3}μs", t);
+
+    if let Some(path) = env::args().nth(1) {
+        let reader: Box<dyn Read> = if path == "-" {
+            Box::new(io::stdin().lock())
+        } else {
+            Box::new(File::open(&path).unwrap_or_else(|e| panic!("Unable to open {}: {}", path, e)))
+        };
+
+        let markers = read_first_markers(BufReader::new(reader), &[4, 14])
+            .unwrap_or_else(|e| panic!("Unable to read {}: {}", path, e));
+
+        for (window_size, marker) in [4, 14].iter().zip(markers) {
+            match marker {
+                Some(pos) => println!("Marker of {} characters: {}", window_size, pos),
+                None => println!("Marker of {} characters: not found", window_size),
+            }
+        }
+    }
 }
 
-fn get_first_marker(input: &str, window_size: usize) -> usize {
-    for i in 0..input.len() - window_size {
-        let window = input
-            .chars()
-            .skip(i)
-            .take(window_size)
-            .collect::<HashSet<char>>();
+/// Finds the end of the first window of distinct bytes, one byte at a time. Only the window
+/// currently made of distinct bytes is tracked, so each byte is processed in constant time.
+struct MarkerDetector {
+    window_size: usize,
+    /// Position right after the last occurrence of each byte, 0 if it wasn't seen yet
+    last_seen: [usize; 256],
+    /// Start of the longest run of distinct bytes ending at the current position
+    start: usize,
+    pos: usize,
+}
 
-        if window.len() == window_size {
-            return i + window_size;
+impl MarkerDetector {
+    fn new(window_size: usize) -> Self {
+        MarkerDetector {
+            window_size,
+            last_seen: [0; 256],
+            start: 0,
+            pos: 0,
         }
     }
 
-    0
+    /// Adds the next byte, returning the position of the marker once it's found
+    fn push(&mut self, b: u8) -> Option<usize> {
+        self.start = self.start.max(self.last_seen[b as usize]);
+        self.pos += 1;
+        self.last_seen[b as usize] = self.pos;
+
+        (self.pos - self.start == self.window_size).then_some(self.pos)
+    }
+}
+
+/// Number of characters processed before the first `window_size` distinct ones, if there are any
+fn get_first_marker(input: &str, window_size: usize) -> Option<usize> {
+    if window_size == 0 {
+        return Some(0);
+    }
+
+    let mut detector = MarkerDetector::new(window_size);
+
+    input.bytes().find_map(|b| detector.push(b))
+}
+
+/// Same as `get_first_marker` for several window sizes at once, reading only as much of the
+/// input as needed
+fn read_first_markers<R: BufRead>(
+    reader: R,
+    window_sizes: &[usize],
+) -> io::Result<Vec<Option<usize>>> {
+    let mut detectors = window_sizes
+        .iter()
+        .map(|&w| MarkerDetector::new(w))
+        .collect::<Vec<_>>();
+    let mut markers = window_sizes
+        .iter()
+        .map(|&w| (w == 0).then_some(0))
+        .collect::<Vec<_>>();
+
+    for b in reader.bytes() {
+        if markers.iter().all(Option::is_some) {
+            break;
+        }
+
+        let b = b?;
+        for (detector, marker) in detectors.iter_mut().zip(markers.iter_mut()) {
+            if marker.is_none() {
+                *marker = detector.push(b);
+            }
+        }
+    }
+
+    Ok(markers)
 }
 
 #[cfg(test)]
 mod tests {
+    use std::io::Cursor;
+
     use super::*;
 
+    fn gfm(input: &str, window_size: usize) -> usize {
+        get_first_marker(input, window_size).unwrap()
+    }
+
     #[test]
     fn test_p1() {
-        assert_eq!(get_first_marker("mjqjpqmgbljsphdztnvjfqwrcgsmlb", 4), 7);
-        assert_eq!(get_first_marker("bvwbjplbgvbhsrlpgdmjqwftvncz", 4), 5);
-        assert_eq!(get_first_marker("nppdvjthqldpwncqszvftbrmjlhg", 4), 6);
-        assert_eq!(get_first_marker("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg", 4), 10);
-        assert_eq!(get_first_marker("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", 4), 11);
+        assert_eq!(gfm("mjqjpqmgbljsphdztnvjfqwrcgsmlb", 4), 7);
+        assert_eq!(gfm("bvwbjplbgvbhsrlpgdmjqwftvncz", 4), 5);
+        assert_eq!(gfm("nppdvjthqldpwncqszvftbrmjlhg", 4), 6);
+        assert_eq!(gfm("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg", 4), 10);
+        assert_eq!(gfm("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", 4), 11);
     }
 
     #[test]
     fn test_p2() {
-        assert_eq!(get_first_marker("mjqjpqmgbljsphdztnvjfqwrcgsmlb", 14), 19);
-        assert_eq!(get_first_marker("bvwbjplbgvbhsrlpgdmjqwftvncz", 14), 23);
-        assert_eq!(get_first_marker("nppdvjthqldpwncqszvftbrmjlhg", 14), 23);
+        assert_eq!(gfm("mjqjpqmgbljsphdztnvjfqwrcgsmlb", 14), 19);
+        assert_eq!(gfm("bvwbjplbgvbhsrlpgdmjqwftvncz", 14), 23);
+        assert_eq!(gfm("nppdvjthqldpwncqszvftbrmjlhg", 14), 23);
+        assert_eq!(gfm("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg", 14), 29);
+        assert_eq!(gfm("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", 14), 26);
+    }
+
+    #[test]
+    fn test_edge_cases() {
+        // The marker can be the very last window
+        assert_eq!(get_first_marker("aabcd", 4), Some(5));
+        assert_eq!(get_first_marker("abcd", 4), Some(4));
+        assert_eq!(get_first_marker("abc", 4), None);
+        assert_eq!(get_first_marker("", 4), None);
+        assert_eq!(get_first_marker("aaaaaaaa", 2), None);
+        assert_eq!(get_first_marker("aaaaaaab", 2), Some(8));
+        assert_eq!(get_first_marker("a", 1), Some(1));
+        assert_eq!(get_first_marker("abc", 0), Some(0));
+        // A repeat outside the window doesn't matter anymore
+        assert_eq!(get_first_marker("abcadef", 4), Some(5));
+        assert_eq!(get_first_marker("abcbdef", 4), Some(6));
+    }
+
+    #[test]
+    fn test_read_first_markers() {
+        let reader = Cursor::new("mjqjpqmgbljsphdztnvjfqwrcgsmlb");
         assert_eq!(
-            get_first_marker("nznrnfrfntjfmvfwmzdfjlvtqnbhcprsg", 14),
-            29
+            read_first_markers(reader, &[4, 14, 0, 40]).unwrap(),
+            vec![Some(7), Some(19), Some(0), None]
         );
-        assert_eq!(get_first_marker("zcfzfwzzqfrljwzlrfnpqdbhtmscgvjw", 14), 26);
+
+        let reader = Cursor::new(vec![0, 255, 0, 128, 7]);
+        assert_eq!(read_first_markers(reader, &[4]).unwrap(), vec![Some(5)]);
     }
 }
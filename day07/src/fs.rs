@@ -0,0 +1,386 @@
+use std::collections::HashSet;
+use std::fmt::{self, Display};
+
+pub type NodeId = usize;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Kind {
+    Dir(Vec<NodeId>),
+    File,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Node {
+    pub name: String,
+    pub parent: Option<NodeId>,
+    pub kind: Kind,
+    /// Size of the file, or total size of everything in the directory
+    pub size: usize,
+}
+
+impl Node {
+    pub fn is_dir(&self) -> bool {
+        matches!(self.kind, Kind::Dir(_))
+    }
+
+    pub fn children(&self) -> &[NodeId] {
+        match &self.kind {
+            Kind::Dir(children) => children,
+            Kind::File => &[],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TranscriptError {
+    pub line: usize,
+    pub msg: String,
+}
+
+impl Display for TranscriptError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Line {}: {}", self.line, self.msg)
+    }
+}
+
+fn describe(kind: &Kind, size: usize) -> String {
+    match kind {
+        Kind::Dir(_) => String::from("a directory"),
+        Kind::File => format!("a file of size {}", size),
+    }
+}
+
+/// Directory tree where each node knows its parent. Nodes are only ever added after their
+/// parent, which lets directory sizes be aggregated in a single backward pass.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileSystem {
+    nodes: Vec<Node>,
+}
+
+impl FileSystem {
+    pub const ROOT: NodeId = 0;
+
+    fn new() -> Self {
+        FileSystem {
+            nodes: vec![Node {
+                name: String::from("/"),
+                parent: None,
+                kind: Kind::Dir(Vec::new()),
+                size: 0,
+            }],
+        }
+    }
+
+    /// Rebuilds the filesystem seen through the `cd` and `ls` commands of a transcript. Listing
+    /// a directory again is fine as long as it agrees with what was listed before. Every invalid
+    /// line is reported, not just the first one.
+    pub fn from_transcript(input: &[String]) -> Result<Self, Vec<TranscriptError>> {
+        let mut fs = FileSystem::new();
+        let mut cwd = FileSystem::ROOT;
+        // Names listed by the current `ls`, if its output is being read
+        let mut listing: Option<HashSet<&str>> = None;
+        let mut errors = Vec::new();
+
+        for (idx, line) in input.iter().enumerate().filter(|(_, l)| !l.is_empty()) {
+            let result = match line.strip_prefix("$ ") {
+                Some(cmd) => {
+                    listing = None;
+
+                    match cmd.split_whitespace().collect::<Vec<_>>().as_slice() {
+                        ["cd", dir] => fs.cd(cwd, dir).map(|dir| cwd = dir),
+                        ["ls"] => {
+                            listing = Some(HashSet::new());
+                            Ok(())
+                        }
+                        ["cd", ..] | ["ls", ..] => Err(format!("Invalid arguments: '{}'", cmd)),
+                        _ => Err(format!("Unknown command: '{}'", cmd)),
+                    }
+                }
+                None => match &mut listing {
+                    Some(names) => fs.add_entry(cwd, line, names),
+                    None => Err(format!("Output outside of ls: '{}'", line)),
+                },
+            };
+
+            if let Err(msg) = result {
+                errors.push(TranscriptError { line: idx + 1, msg });
+            }
+        }
+
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        fs.aggregate_sizes();
+
+        Ok(fs)
+    }
+
+    fn cd(&self, cwd: NodeId, arg: &str) -> Result<NodeId, String> {
+        match arg {
+            "/" => Ok(FileSystem::ROOT),
+            ".." => self.nodes[cwd]
+                .parent
+                .ok_or_else(|| String::from("Can't go above /")),
+            name => match self.child(cwd, name) {
+                Some(id) if self.nodes[id].is_dir() => Ok(id),
+                Some(id) => Err(format!("{} is not a directory", self.path(id))),
+                None => Err(format!(
+                    "No directory {} listed in {}",
+                    name,
+                    self.path(cwd)
+                )),
+            },
+        }
+    }
+
+    /// Adds a line of `ls` output to `dir`, `names` being what the current listing already had
+    fn add_entry<'a>(
+        &mut self,
+        dir: NodeId,
+        line: &'a str,
+        names: &mut HashSet<&'a str>,
+    ) -> Result<(), String> {
+        let (kind, size, name) = match line.split_once(' ') {
+            Some(("dir", name)) => (Kind::Dir(Vec::new()), 0, name),
+            Some((size, name)) => match size.parse() {
+                Ok(size) => (Kind::File, size, name),
+                Err(_) => return Err(format!("Invalid size: '{}'", size)),
+            },
+            None => return Err(format!("Invalid listing entry: '{}'", line)),
+        };
+
+        if name.is_empty() || name.contains('/') || name == "." || name == ".." {
+            return Err(format!("Invalid name: '{}'", name));
+        }
+
+        if !names.insert(name) {
+            return Err(format!("{} is listed twice", name));
+        }
+
+        self.add(dir, name, kind, size).map(|_| ())
+    }
+
+    /// Adds a node to a directory, unless it already holds the same one from an earlier listing
+    fn add(&mut self, dir: NodeId, name: &str, kind: Kind, size: usize) -> Result<NodeId, String> {
+        if let Some(id) = self.child(dir, name) {
+            let node = &self.nodes[id];
+
+            return match (&node.kind, &kind) {
+                (Kind::Dir(_), Kind::Dir(_)) => Ok(id),
+                (Kind::File, Kind::File) if node.size == size => Ok(id),
+                _ => Err(format!(
+                    "{} was listed as {}, now as {}",
+                    self.path(id),
+                    describe(&node.kind, node.size),
+                    describe(&kind, size)
+                )),
+            };
+        }
+
+        let id = self.nodes.len();
+        self.nodes.push(Node {
+            name: name.to_string(),
+            parent: Some(dir),
+            kind,
+            size,
+        });
+
+        if let Kind::Dir(children) = &mut self.nodes[dir].kind {
+            children.push(id);
+        }
+
+        Ok(id)
+    }
+
+    fn aggregate_sizes(&mut self) {
+        for id in (1..self.nodes.len()).rev() {
+            let (size, parent) = (self.nodes[id].size, self.nodes[id].parent);
+            if let Some(parent) = parent {
+                self.nodes[parent].size += size;
+            }
+        }
+    }
+
+    pub fn node(&self, id: NodeId) -> &Node {
+        &self.nodes[id]
+    }
+
+    pub fn child(&self, dir: NodeId, name: &str) -> Option<NodeId> {
+        self.nodes[dir]
+            .children()
+            .iter()
+            .copied()
+            .find(|&c| self.nodes[c].name == name)
+    }
+
+    /// Finds a node from its absolute path, like `/a/e`
+    pub fn lookup(&self, path: &str) -> Option<NodeId> {
+        path.strip_prefix('/')?
+            .split('/')
+            .filter(|name| !name.is_empty())
+            .try_fold(FileSystem::ROOT, |dir, name| self.child(dir, name))
+    }
+
+    /// Absolute path of a node, like `/a/e`
+    pub fn path(&self, id: NodeId) -> String {
+        let mut names = Vec::new();
+        let mut cur = id;
+
+        while let Some(parent) = self.nodes[cur].parent {
+            names.push(self.nodes[cur].name.as_str());
+            cur = parent;
+        }
+
+        names.reverse();
+        format!("/{}", names.join("/"))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (NodeId, &Node)> {
+        self.nodes.iter().enumerate()
+    }
+
+    pub fn dirs(&self) -> impl Iterator<Item = (NodeId, &Node)> {
+        self.iter().filter(|(_, n)| n.is_dir())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(s: &str) -> Vec<String> {
+        s.lines().map(String::from).collect()
+    }
+
+    fn errors(s: &str) -> Vec<String> {
+        FileSystem::from_transcript(&lines(s))
+            .unwrap_err()
+            .iter()
+            .map(|e| e.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_empty_directories() {
+        let fs = FileSystem::from_transcript(&lines(
+            "$ cd /
+$ ls
+dir a
+dir b
+1000 c
+$ cd b
+$ ls
+dir x
+",
+        ))
+        .unwrap();
+
+        let a = fs.lookup("/a").unwrap();
+        assert!(fs.node(a).is_dir());
+        assert_eq!(fs.node(a).size, 0);
+        assert_eq!(fs.node(a).parent, Some(FileSystem::ROOT));
+
+        let x = fs.lookup("/b/x/").unwrap();
+        assert_eq!(fs.node(x).children(), &[]);
+        assert_eq!(fs.node(fs.node(x).parent.unwrap()).name, "b");
+
+        assert_eq!(fs.node(FileSystem::ROOT).size, 1000);
+        assert_eq!(fs.dirs().count(), 4);
+    }
+
+    #[test]
+    fn test_lookup() {
+        let fs = FileSystem::from_transcript(&lines(
+            "$ cd /
+$ ls
+dir a
+$ cd a
+$ ls
+5 b.txt
+",
+        ))
+        .unwrap();
+
+        assert_eq!(fs.lookup("/"), Some(FileSystem::ROOT));
+        assert_eq!(fs.path(FileSystem::ROOT), "/");
+        assert_eq!(fs.path(fs.lookup("/a/b.txt").unwrap()), "/a/b.txt");
+        assert_eq!(fs.lookup("/a/b.txt").map(|id| fs.node(id).size), Some(5));
+        assert_eq!(fs.lookup("/a/b.txt/c"), None);
+        assert_eq!(fs.lookup("/b"), None);
+        assert_eq!(fs.lookup("a"), None);
+    }
+
+    #[test]
+    fn test_repeated_listings() {
+        let fs = FileSystem::from_transcript(&lines(
+            "$ cd /
+$ ls
+dir a
+10 b
+$ cd a
+$ ls
+20 c
+$ cd ..
+$ ls
+10 b
+dir a
+$ cd a
+$ ls
+20 c
+",
+        ))
+        .unwrap();
+
+        assert_eq!(fs.iter().count(), 4);
+        assert_eq!(fs.node(FileSystem::ROOT).size, 30);
+    }
+
+    #[test]
+    fn test_errors() {
+        assert_eq!(
+            errors(
+                "$ cd /
+$ ls
+dir a
+10 b
+$ cd b
+$ cd c
+$ cd ..
+$ mkdir x
+$ ls -l
+12 d
+$ ls
+10 b
+10 b
+20 a
+x y
+-5 z
+dir ..
+dir"
+            ),
+            vec![
+                "Line 5: /b is not a directory",
+                "Line 6: No directory c listed in /",
+                "Line 7: Can't go above /",
+                "Line 8: Unknown command: 'mkdir x'",
+                "Line 9: Invalid arguments: 'ls -l'",
+                "Line 10: Output outside of ls: '12 d'",
+                "Line 13: b is listed twice",
+                "Line 14: /a was listed as a directory, now as a file of size 20",
+                "Line 15: Invalid size: 'x'",
+                "Line 16: Invalid size: '-5'",
+                "Line 17: Invalid name: '..'",
+                "Line 18: Invalid listing entry: 'dir'",
+            ]
+        );
+
+        assert_eq!(
+            errors("$ ls\n5 a\n$ ls\n6 a\n$ cd"),
+            vec![
+                "Line 4: /a was listed as a file of size 5, now as a file of size 6",
+                "Line 5: Invalid arguments: 'cd'"
+            ]
+        );
+    }
+}
@@ -1,14 +1,27 @@
-use std::collections::HashMap;
+use std::env;
 use std::fmt::Display;
-use std::path::PathBuf;
 use std::time::Instant;
 
+use aoc_common::cli::options;
 use aoc_common::get_input;
+use itertools::Itertools;
+
+use crate::fs::FileSystem;
+use crate::report::{largest_dirs, smallest_dir_freeing, sum_of_dirs_at_most, tree};
+
+mod fs;
+mod report;
+
+const SMALL_DIR_MAX_SIZE: usize = 100_000;
+const DISK_SIZE: usize = 70_000_000;
+const UPDATE_SIZE: usize = 30_000_000;
 
 fn solve(input: &[String]) -> (impl Display, impl Display) {
-    let files = parse_directory_structure(input);
-    let p1 = get_sum_of_small_directories(&files);
-    let p2 = get_size_of_dir_to_delete(&files);
+    let fs = parse_transcript(input);
+    let p1 = sum_of_dirs_at_most(&fs, SMALL_DIR_MAX_SIZE);
+    let p2 = smallest_dir_freeing(&fs, DISK_SIZE, UPDATE_SIZE)
+        .unwrap_or_else(|e| panic!("{}", e))
+        .map_or(0, |id| fs.node(id).size);
 
     (p1, p2)
 }
@@ -25,92 +38,98 @@ fn main() {
     println!("Part 1: {}", r1);
     println!("Part 2: {}", r2);
     println!("Duration: {:.3}μs", t);
-}
 
-struct File {
-    path: PathBuf,
-    size: usize,
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    if !args.is_empty() {
+        let opts = Options::from_args(&args).unwrap_or_else(|e| panic!("{}", e));
+        report(&parse_transcript(&input), &opts).unwrap_or_else(|e| panic!("{}", e));
+    }
 }
 
-fn parse_directory_structure(input: &[String]) -> Vec<File> {
-    let mut files = Vec::new();
-    let mut path = PathBuf::from("/");
-
-    let mut cmd_iter = input.iter();
-
-    loop {
-        let line = cmd_iter.next();
-
-        if line.is_none() {
-            break;
-        }
+fn parse_transcript(input: &[String]) -> FileSystem {
+    FileSystem::from_transcript(input).unwrap_or_else(|errors| {
+        panic!(
+            "Invalid transcript:\n{}",
+            errors.iter().map(|e| e.to_string()).join("\n")
+        )
+    })
+}
 
-        let cmd = line.unwrap();
+struct Options {
+    tree: Option<String>,
+    du: Option<String>,
+    largest: Option<usize>,
+    max_size: Option<usize>,
+    needed: Option<usize>,
+    disk_size: usize,
+}
 
-        if let Some(cd_arg) = cmd.strip_prefix("$ cd ") {
-            if cd_arg.starts_with('/') {
-                path = PathBuf::from(cd_arg);
-            } else if cd_arg == ".." {
-                path.pop();
-            } else {
-                path.push(cd_arg);
+impl Options {
+    /// Parses `[--tree PATH] [--du PATH] [--largest N] [--max-size N] [--free N [--disk N]]`
+    fn from_args(args: &[String]) -> Result<Self, String> {
+        let mut opts = Options {
+            tree: None,
+            du: None,
+            largest: None,
+            max_size: None,
+            needed: None,
+            disk_size: DISK_SIZE,
+        };
+
+        for opt in options(args) {
+            let opt = opt?;
+
+            match opt.name {
+                "--tree" => opts.tree = Some(opt.value.to_string()),
+                "--du" => opts.du = Some(opt.value.to_string()),
+                "--largest" => opts.largest = Some(opt.parse()?),
+                "--max-size" => opts.max_size = Some(opt.parse()?),
+                "--free" => opts.needed = Some(opt.parse()?),
+                "--disk" => opts.disk_size = opt.parse()?,
+                _ => return Err(opt.unknown()),
             }
-        } else if cmd == "$ ls" || cmd.starts_with("dir ") {
-            continue;
-        } else {
-            let (size, name) = cmd.split_once(' ').unwrap();
-            let size: usize = size.parse().unwrap();
-            files.push(File {
-                path: path.clone().join(name),
-                size,
-            })
         }
-    }
 
-    files
+        Ok(opts)
+    }
 }
 
-fn get_directory_sizes(files: &[File]) -> HashMap<String, usize> {
-    let mut dir_sizes: HashMap<String, usize> = HashMap::new();
-
-    for f in files {
-        let mut p = f.path.clone();
-        p.pop();
+fn report(fs: &FileSystem, opts: &Options) -> Result<(), String> {
+    let lookup = |path: &str| {
+        fs.lookup(path)
+            .ok_or_else(|| format!("{}: no such file or directory", path))
+    };
 
-        loop {
-            let k = p.clone().to_str().unwrap().to_string();
-            let s = dir_sizes.get(&k).unwrap_or(&0) + f.size;
-            dir_sizes.insert(k, s);
+    if let Some(path) = &opts.tree {
+        print!("{}", tree(fs, lookup(path)?));
+    }
 
-            if p.to_str().unwrap() == "/" {
-                break;
-            }
+    if let Some(path) = &opts.du {
+        println!("{}\t{}", fs.node(lookup(path)?).size, path);
+    }
 
-            p.pop();
+    if let Some(n) = opts.largest {
+        for (path, size) in largest_dirs(fs, n) {
+            println!("{}\t{}", size, path);
         }
     }
 
-    dir_sizes
-}
-
-fn get_sum_of_small_directories(files: &[File]) -> usize {
-    get_directory_sizes(files)
-        .iter()
-        .map(|(_, &v)| v)
-        .filter(|&s| s <= 100000)
-        .sum()
-}
+    if let Some(max_size) = opts.max_size {
+        println!(
+            "Directories of at most {}: {}",
+            max_size,
+            sum_of_dirs_at_most(fs, max_size)
+        );
+    }
 
-fn get_size_of_dir_to_delete(files: &[File]) -> usize {
-    let dir_sizes = get_directory_sizes(files);
-    let total_size = dir_sizes.get(&String::from("/")).unwrap();
+    if let Some(needed) = opts.needed {
+        match smallest_dir_freeing(fs, opts.disk_size, needed)? {
+            Some(id) => println!("Delete {}\t{}", fs.path(id), fs.node(id).size),
+            None => println!("{} is already free", needed),
+        }
+    }
 
-    dir_sizes
-        .iter()
-        .map(|(_, &s)| s)
-        .filter(|&s| total_size - s <= 40000000)
-        .min()
-        .unwrap()
+    Ok(())
 }
 
 #[cfg(test)]
@@ -145,16 +164,21 @@ $ ls
 ";
 
     #[test]
-    fn test_parse_directory_structure() {
+    fn test_from_transcript() {
         let input = TEST_INPUT
             .split('\n')
             .filter(|s| !s.is_empty())
             .map(String::from)
             .collect_vec();
 
-        let files = parse_directory_structure(&input);
+        let fs = FileSystem::from_transcript(&input).unwrap();
 
-        assert_eq!(files.len(), 10);
+        assert_eq!(fs.iter().filter(|(_, n)| !n.is_dir()).count(), 10);
+        assert_eq!(fs.dirs().count(), 4);
+        assert_eq!(fs.node(fs.lookup("/a/e").unwrap()).size, 584);
+        assert_eq!(fs.node(fs.lookup("/a").unwrap()).size, 94853);
+        assert_eq!(fs.node(fs.lookup("/d").unwrap()).size, 24933642);
+        assert_eq!(fs.node(FileSystem::ROOT).size, 48381165);
     }
 
     #[test]
@@ -165,9 +189,9 @@ $ ls
             .map(String::from)
             .collect_vec();
 
-        let files = parse_directory_structure(&input);
+        let fs = FileSystem::from_transcript(&input).unwrap();
 
-        assert_eq!(get_sum_of_small_directories(&files), 95437);
+        assert_eq!(sum_of_dirs_at_most(&fs, SMALL_DIR_MAX_SIZE), 95437);
     }
 
     #[test]
@@ -178,8 +202,13 @@ $ ls
             .map(String::from)
             .collect_vec();
 
-        let files = parse_directory_structure(&input);
+        let fs = FileSystem::from_transcript(&input).unwrap();
+
+        let dir = smallest_dir_freeing(&fs, DISK_SIZE, UPDATE_SIZE)
+            .unwrap()
+            .unwrap();
 
-        assert_eq!(get_size_of_dir_to_delete(&files), 24933642);
+        assert_eq!(fs.path(dir), "/d");
+        assert_eq!(fs.node(dir).size, 24933642);
     }
 }
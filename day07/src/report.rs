@@ -0,0 +1,203 @@
+use std::fmt::Write;
+
+use itertools::Itertools;
+
+use crate::fs::{FileSystem, Kind, NodeId};
+
+/// Draws the tree under `id` the way the puzzle does, with directory sizes and the entries of
+/// each directory sorted by name
+pub fn tree(fs: &FileSystem, id: NodeId) -> String {
+    let mut out = String::new();
+    tree_rec(fs, id, 0, &mut out);
+
+    out
+}
+
+fn tree_rec(fs: &FileSystem, id: NodeId, depth: usize, out: &mut String) {
+    let node = fs.node(id);
+    let kind = match node.kind {
+        Kind::Dir(_) => "dir",
+        Kind::File => "file",
+    };
+
+    writeln!(
+        out,
+        "{:indent$}- {} ({}, size={})",
+        "",
+        node.name,
+        kind,
+        node.size,
+        indent = depth * 2
+    )
+    .unwrap();
+
+    for &child in node.children().iter().sorted_by_key(|&&c| &fs.node(c).name) {
+        tree_rec(fs, child, depth + 1, out);
+    }
+}
+
+/// Paths and sizes of the `n` largest directories, largest first
+pub fn largest_dirs(fs: &FileSystem, n: usize) -> Vec<(String, usize)> {
+    fs.dirs()
+        .sorted_by(|(_, a), (_, b)| b.size.cmp(&a.size))
+        .take(n)
+        .map(|(id, d)| (fs.path(id), d.size))
+        .collect()
+}
+
+/// Total size of the directories of at most `max_size`, counting nested ones several times
+pub fn sum_of_dirs_at_most(fs: &FileSystem, max_size: usize) -> usize {
+    fs.dirs()
+        .map(|(_, d)| d.size)
+        .filter(|&s| s <= max_size)
+        .sum()
+}
+
+/// Smallest directory to delete so that a disk of `disk_size` has at least `needed` free, or
+/// `None` when there is already enough free space. Fails if even deleting everything isn't
+/// enough, or if the files don't fit on the disk in the first place.
+pub fn smallest_dir_freeing(
+    fs: &FileSystem,
+    disk_size: usize,
+    needed: usize,
+) -> Result<Option<NodeId>, String> {
+    let used = fs.node(FileSystem::ROOT).size;
+    let free = disk_size
+        .checked_sub(used)
+        .ok_or_else(|| format!("{} of files don't fit on a disk of {}", used, disk_size))?;
+
+    if free >= needed {
+        return Ok(None);
+    }
+    if needed > disk_size {
+        return Err(format!(
+            "Unable to free {} on a disk of {}",
+            needed, disk_size
+        ));
+    }
+
+    let to_free = needed - free;
+    let dir = fs
+        .dirs()
+        .filter(|(_, d)| d.size >= to_free)
+        .min_by_key(|(_, d)| d.size)
+        .map(|(id, _)| id);
+
+    Ok(dir)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_INPUT: &str = "$ cd /
+$ ls
+dir a
+14848514 b.txt
+8504156 c.dat
+dir d
+$ cd a
+$ ls
+dir e
+29116 f
+2557 g
+62596 h.lst
+$ cd e
+$ ls
+584 i
+$ cd ..
+$ cd ..
+$ cd d
+$ ls
+4060174 j
+8033020 d.log
+5626152 d.ext
+7214296 k
+";
+
+    fn fs() -> FileSystem {
+        FileSystem::from_transcript(&TEST_INPUT.lines().map(String::from).collect_vec()).unwrap()
+    }
+
+    #[test]
+    fn test_tree() {
+        let fs = fs();
+
+        assert_eq!(
+            tree(&fs, FileSystem::ROOT),
+            "- / (dir, size=48381165)
+  - a (dir, size=94853)
+    - e (dir, size=584)
+      - i (file, size=584)
+    - f (file, size=29116)
+    - g (file, size=2557)
+    - h.lst (file, size=62596)
+  - b.txt (file, size=14848514)
+  - c.dat (file, size=8504156)
+  - d (dir, size=24933642)
+    - d.ext (file, size=5626152)
+    - d.log (file, size=8033020)
+    - j (file, size=4060174)
+    - k (file, size=7214296)
+"
+        );
+        assert_eq!(
+            tree(&fs, fs.lookup("/a/e").unwrap()),
+            "- e (dir, size=584)\n  - i (file, size=584)\n"
+        );
+    }
+
+    #[test]
+    fn test_largest_dirs() {
+        assert_eq!(
+            largest_dirs(&fs(), 3),
+            vec![
+                (String::from("/"), 48381165),
+                (String::from("/d"), 24933642),
+                (String::from("/a"), 94853)
+            ]
+        );
+        assert_eq!(largest_dirs(&fs(), 10).len(), 4);
+    }
+
+    #[test]
+    fn test_thresholds() {
+        let fs = fs();
+        let size =
+            |res: Result<Option<NodeId>, String>| res.map(|id| id.map(|id| fs.node(id).size));
+
+        assert_eq!(sum_of_dirs_at_most(&fs, 100000), 95437);
+        assert_eq!(sum_of_dirs_at_most(&fs, 1000), 584);
+
+        assert_eq!(
+            size(smallest_dir_freeing(&fs, 70000000, 30000000)),
+            Ok(Some(24933642))
+        );
+        assert_eq!(
+            size(smallest_dir_freeing(&fs, 70000000, 21700000)),
+            Ok(Some(94853))
+        );
+        assert_eq!(size(smallest_dir_freeing(&fs, 48381166, 2)), Ok(Some(584)));
+        assert_eq!(
+            size(smallest_dir_freeing(&fs, 48381166, 48381166)),
+            Ok(Some(48381165))
+        );
+
+        // Nothing to delete, even though some directories are empty or small
+        assert_eq!(size(smallest_dir_freeing(&fs, 100000000, 1)), Ok(None));
+        assert_eq!(size(smallest_dir_freeing(&fs, 48381165, 0)), Ok(None));
+
+        assert_eq!(
+            size(smallest_dir_freeing(&fs, 50000000, 60000000)),
+            Err(String::from(
+                "Unable to free 60000000 on a disk of 50000000"
+            ))
+        );
+        assert_eq!(
+            size(smallest_dir_freeing(&fs, 1000, 0)),
+            Err(String::from(
+                "48381165 of files don't fit on a disk of 1000"
+            ))
+        );
+    }
+}
@@ -0,0 +1,313 @@
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    North,
+    South,
+    West,
+    East,
+}
+
+impl Direction {
+    pub const ALL: [Direction; 4] = [
+        Direction::North,
+        Direction::South,
+        Direction::West,
+        Direction::East,
+    ];
+}
+
+/// Set of directions a tree is visible from, as a bitmask
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Visibility(u8);
+
+impl Visibility {
+    fn insert(&mut self, dir: Direction) {
+        self.0 |= 1 << dir as u8;
+    }
+
+    pub fn contains(&self, dir: Direction) -> bool {
+        self.0 & (1 << dir as u8) != 0
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.0 != 0
+    }
+
+    /// Number of directions the tree is visible from
+    pub fn count(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+}
+
+/// What can be seen from a tree, looking in each direction
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct View {
+    /// Number of trees seen before the view is blocked or the edge is reached
+    distance: [u32; 4],
+    /// Directions where every tree up to the edge is shorter, so the tree is visible from outside
+    visibility: Visibility,
+}
+
+impl View {
+    pub fn distance(&self, dir: Direction) -> usize {
+        self.distance[dir as usize] as usize
+    }
+
+    pub fn visibility(&self) -> Visibility {
+        self.visibility
+    }
+
+    pub fn is_visible(&self) -> bool {
+        self.visibility.is_visible()
+    }
+
+    pub fn scenic_score(&self) -> usize {
+        self.distance.iter().map(|&d| d as usize).product()
+    }
+}
+
+/// Grid of tree heights, with the view from every tree computed once when parsing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Forest {
+    width: usize,
+    height: usize,
+    heights: Vec<u8>,
+    views: Vec<View>,
+}
+
+impl Forest {
+    fn new(width: usize, height: usize, heights: Vec<u8>) -> Self {
+        let mut forest = Forest {
+            width,
+            height,
+            heights,
+            views: vec![View::default(); width * height],
+        };
+
+        let mut stack = Vec::new();
+
+        for y in 0..height {
+            let row = y * width..(y + 1) * width;
+            forest.look(Direction::West, row.clone(), &mut stack);
+            forest.look(Direction::East, row.rev(), &mut stack);
+        }
+
+        for x in 0..width {
+            let col = (x..width * height).step_by(width);
+            forest.look(Direction::North, col.clone(), &mut stack);
+            forest.look(Direction::South, col.rev(), &mut stack);
+        }
+
+        forest
+    }
+
+    /// Fills the views towards `dir` for a line of trees, given from the edge in that direction.
+    /// The stack keeps the trees that can still block the view, tallest at the bottom, so every
+    /// tree is pushed and popped at most once. The stack is only passed in to reuse its buffer.
+    fn look<I: Iterator<Item = usize>>(
+        &mut self,
+        dir: Direction,
+        line: I,
+        stack: &mut Vec<(usize, u8)>,
+    ) {
+        stack.clear();
+
+        for (pos, idx) in line.enumerate() {
+            let h = self.heights[idx];
+
+            while stack.last().is_some_and(|&(_, sh)| sh < h) {
+                stack.pop();
+            }
+
+            let view = &mut self.views[idx];
+            match stack.last() {
+                Some(&(blocker, _)) => view.distance[dir as usize] = (pos - blocker) as u32,
+                None => {
+                    view.distance[dir as usize] = pos as u32;
+                    view.visibility.insert(dir);
+                }
+            }
+
+            stack.push((pos, h));
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn tree_height(&self, x: usize, y: usize) -> u8 {
+        self.heights[y * self.width + x]
+    }
+
+    pub fn view(&self, x: usize, y: usize) -> View {
+        self.views[y * self.width + x]
+    }
+
+    pub fn visible_count(&self) -> usize {
+        self.views.iter().filter(|v| v.is_visible()).count()
+    }
+
+    /// Position of the tree with the highest scenic score, the first one in reading order on ties
+    pub fn best_spot(&self) -> (usize, usize) {
+        let idx = (0..self.views.len())
+            .rev()
+            .max_by_key(|&i| self.views[i].scenic_score())
+            .unwrap_or(0);
+
+        (idx % self.width, idx / self.width)
+    }
+
+    pub fn best_scenic_score(&self) -> usize {
+        let (x, y) = self.best_spot();
+
+        self.view(x, y).scenic_score()
+    }
+
+    /// Directions each tree is visible from, row by row
+    pub fn visibility_mask(&self) -> Vec<Vec<Visibility>> {
+        self.views
+            .chunks(self.width)
+            .map(|row| row.iter().map(View::visibility).collect())
+            .collect()
+    }
+
+    /// Scenic score of each tree, row by row
+    pub fn scenic_scores(&self) -> Vec<Vec<usize>> {
+        self.views
+            .chunks(self.width)
+            .map(|row| row.iter().map(View::scenic_score).collect())
+            .collect()
+    }
+}
+
+impl FromStr for Forest {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let lines = s.lines().filter(|l| !l.is_empty()).collect::<Vec<_>>();
+        let width = lines.first().map_or(0, |l| l.chars().count());
+
+        if width == 0 {
+            return Err(String::from("Empty forest"));
+        }
+
+        let mut heights = Vec::with_capacity(width * lines.len());
+
+        for (y, line) in lines.iter().enumerate() {
+            for (x, c) in line.chars().enumerate() {
+                let h = c.to_digit(10).ok_or_else(|| {
+                    format!("Invalid height '{}' at row {}, column {}", c, y + 1, x + 1)
+                })?;
+                heights.push(h as u8);
+            }
+
+            if heights.len() != (y + 1) * width {
+                return Err(format!(
+                    "Row {} has {} trees instead of {}",
+                    y + 1,
+                    line.chars().count(),
+                    width
+                ));
+            }
+        }
+
+        Ok(Forest::new(width, lines.len(), heights))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_INPUT: &str = "30373
+25512
+65332
+33549
+35390
+";
+
+    #[test]
+    fn test_views() {
+        let forest = TEST_INPUT.parse::<Forest>().unwrap();
+
+        let view = forest.view(2, 1);
+        assert_eq!(Direction::ALL.map(|d| view.distance(d)), [1, 2, 1, 2]);
+        assert_eq!(view.scenic_score(), 4);
+        assert!(view.visibility().contains(Direction::North));
+        assert!(!view.visibility().contains(Direction::South));
+        assert_eq!(view.visibility().count(), 2);
+
+        let view = forest.view(2, 3);
+        assert_eq!(Direction::ALL.map(|d| view.distance(d)), [2, 1, 2, 2]);
+        assert_eq!(view.scenic_score(), 8);
+
+        // The 3 in the middle is the only hidden tree of the middle row
+        let visible = (0..5)
+            .map(|x| forest.view(x, 2).is_visible())
+            .collect::<Vec<_>>();
+        assert_eq!(visible, vec![true, true, false, true, true]);
+
+        let corner = forest.view(0, 0);
+        assert_eq!(corner.scenic_score(), 0);
+        assert_eq!(
+            Direction::ALL.map(|d| corner.visibility().contains(d)),
+            [true, false, true, false]
+        );
+    }
+
+    #[test]
+    fn test_matrices() {
+        let forest = TEST_INPUT.parse::<Forest>().unwrap();
+
+        assert_eq!(forest.best_spot(), (2, 3));
+        assert_eq!(
+            forest.scenic_scores(),
+            vec![
+                vec![0, 0, 0, 0, 0],
+                vec![0, 1, 4, 1, 0],
+                vec![0, 6, 1, 2, 0],
+                vec![0, 1, 8, 3, 0],
+                vec![0, 0, 0, 0, 0],
+            ]
+        );
+
+        let counts = forest
+            .visibility_mask()
+            .iter()
+            .map(|row| row.iter().map(Visibility::count).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            counts,
+            vec![
+                vec![2, 1, 1, 3, 2],
+                vec![1, 2, 2, 0, 1],
+                vec![4, 1, 0, 1, 1],
+                vec![1, 0, 2, 0, 4],
+                vec![2, 2, 1, 4, 2],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            "123\n4a6\n".parse::<Forest>(),
+            Err(String::from("Invalid height 'a' at row 2, column 2"))
+        );
+        assert_eq!(
+            "123\n45\n".parse::<Forest>(),
+            Err(String::from("Row 2 has 2 trees instead of 3"))
+        );
+        assert_eq!(
+            "123\n4567\n".parse::<Forest>(),
+            Err(String::from("Row 2 has 4 trees instead of 3"))
+        );
+        assert_eq!("".parse::<Forest>(), Err(String::from("Empty forest")));
+    }
+}
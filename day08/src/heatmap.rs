@@ -0,0 +1,89 @@
+use std::io::{self, Write};
+
+/// 256-color terminal palette going from dark blue to red
+const PALETTE: [u8; 12] = [17, 18, 19, 25, 31, 37, 43, 78, 148, 184, 214, 196];
+
+/// Index of `value` in a palette of `len` colors, where 0 gets the first color and `max` the last
+fn level(value: usize, max: usize, len: usize) -> usize {
+    if max == 0 {
+        return 0;
+    }
+
+    (value * (len - 1) + max / 2) / max
+}
+
+/// Draws the matrix with one colored cell per value, marking `highlight` with `<>`
+pub fn terminal_heatmap(values: &[Vec<usize>], highlight: Option<(usize, usize)>) -> String {
+    let max = values.iter().flatten().copied().max().unwrap_or(0);
+    let mut out = String::new();
+
+    for (y, row) in values.iter().enumerate() {
+        for (x, &v) in row.iter().enumerate() {
+            let color = PALETTE[level(v, max, PALETTE.len())];
+            let cell = if highlight == Some((x, y)) {
+                "<>"
+            } else {
+                "  "
+            };
+            out.push_str(&format!("\x1b[48;5;{}m{}", color, cell));
+        }
+        out.push_str("\x1b[0m\n");
+    }
+
+    out
+}
+
+/// Writes the matrix as a binary (P5) graymap, black for 0 and white for the highest value,
+/// each value scaled to a `scale` x `scale` square
+pub fn write_pgm<W: Write>(values: &[Vec<usize>], out: &mut W, scale: usize) -> io::Result<()> {
+    let scale = scale.max(1);
+    let max = values.iter().flatten().copied().max().unwrap_or(0);
+    let (w, h) = (values.first().map_or(0, Vec::len), values.len());
+
+    write!(out, "P5\n{} {}\n255\n", w * scale, h * scale)?;
+
+    for row in values {
+        let line = row
+            .iter()
+            .flat_map(|&v| std::iter::repeat_n(level(v, max, 256) as u8, scale))
+            .collect::<Vec<u8>>();
+
+        for _ in 0..scale {
+            out.write_all(&line)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_terminal_heatmap() {
+        let values = vec![vec![0, 4], vec![8, 2]];
+
+        assert_eq!(
+            terminal_heatmap(&values, Some((0, 1))),
+            "\x1b[48;5;17m  \x1b[48;5;43m  \x1b[0m\n\x1b[48;5;196m<>\x1b[48;5;25m  \x1b[0m\n"
+        );
+        assert!(terminal_heatmap(&[vec![0, 0]], None).starts_with("\x1b[48;5;17m  "));
+    }
+
+    #[test]
+    fn test_write_pgm() {
+        let mut pgm = Vec::new();
+        write_pgm(&[vec![0, 1], vec![2, 4]], &mut pgm, 2).unwrap();
+
+        let header = b"P5\n4 4\n255\n";
+        assert!(pgm.starts_with(header));
+        assert_eq!(
+            &pgm[header.len()..],
+            &[
+                0, 0, 64, 64, 0, 0, 64, 64, //
+                128, 128, 255, 255, 128, 128, 255, 255
+            ]
+        );
+    }
+}
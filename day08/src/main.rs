@@ -1,12 +1,22 @@
+use std::env;
 use std::fmt::Display;
+use std::fs::File;
+use std::io::BufWriter;
 use std::time::Instant;
 
+use aoc_common::cli::options;
 use aoc_common::get_input;
-use itertools::Itertools;
+
+use crate::forest::{Direction, Forest, Visibility};
+use crate::heatmap::{terminal_heatmap, write_pgm};
+
+mod forest;
+mod heatmap;
 
 fn solve(input: &[String]) -> (impl Display, impl Display) {
-    let p1 = get_number_of_visible_trees(input);
-    let p2 = get_best_scenic_score(input);
+    let forest = parse_forest(input);
+    let p1 = forest.visible_count();
+    let p2 = forest.best_scenic_score();
 
     assert_eq!(p1, 1713);
     assert_eq!(p2, 268464);
@@ -26,155 +36,108 @@ fn main() {
     println!("Part 1: {}", r1);
     println!("Part 2: {}", r2);
     println!("Duration: {:.3}μs", t);
-}
-
-#[derive(Debug)]
-struct Tree {
-    height: i8,
-    visible: bool,
-}
 
-type Forest = Vec<Vec<Tree>>;
-
-fn get_number_of_visible_trees(input: &[String]) -> usize {
-    let mut forest = input
-        .iter()
-        .map(|s| {
-            s.chars()
-                .map(|c| Tree {
-                    height: c as i8 - '0' as i8,
-                    visible: false,
-                })
-                .collect_vec()
-        })
-        .collect_vec();
-
-    let w = forest[0].len();
-    let h = forest.len();
-
-    for r in forest.iter_mut() {
-        let mut max = -1;
-
-        for mut tree in r.iter_mut() {
-            if tree.height > max {
-                tree.visible = true;
-                max = tree.height;
-            }
-        }
-
-        max = -1;
-
-        for mut tree in r.iter_mut().rev() {
-            if tree.height > max {
-                tree.visible = true;
-                max = tree.height;
-            }
-        }
-    }
-
-    for x in 0..w {
-        let mut max = -1;
-
-        for row in forest.iter_mut() {
-            let mut tree = &mut row[x];
-            if tree.height > max {
-                tree.visible = true;
-                max = tree.height;
-            }
-        }
-
-        max = -1;
-
-        for y in (0..h).rev() {
-            let mut tree = &mut forest[y][x];
-            if tree.height > max {
-                tree.visible = true;
-                max = tree.height;
-            }
-        }
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    if !args.is_empty() {
+        let forest = parse_forest(&input);
+        inspect(&forest, &args).unwrap_or_else(|e| panic!("{}", e));
     }
-
-    let visible: usize = forest
-        .into_iter()
-        .map(|r| r.iter().filter(|t| t.visible).count())
-        .sum();
-
-    visible
 }
 
-fn get_best_scenic_score(input: &[String]) -> usize {
-    let forest = parse_forest(input);
-
-    let w = forest[0].len();
-    let h = forest.len();
-
-    let mut score: usize = 0;
-
-    for x in 1..w - 1 {
-        for y in 1..h - 1 {
-            let s = get_scenic_score(&forest, x, y, w, h);
-
-            if s > score {
-                score = s;
-            }
+/// Handles `[--view X,Y] [--heatmap scenic|visibility] [--scenic-pgm FILE]
+/// [--visibility-pgm FILE] [--scale N]`, where the visibility maps show from how many directions
+/// each tree can be seen
+fn inspect(forest: &Forest, args: &[String]) -> Result<(), String> {
+    let mut scale = 4;
+    let mut pgms = Vec::new();
+
+    let visibility = || {
+        forest
+            .visibility_mask()
+            .iter()
+            .map(|row| row.iter().map(Visibility::count).collect())
+            .collect::<Vec<_>>()
+    };
+
+    for opt in options(args) {
+        let opt = opt?;
+
+        match opt.name {
+            "--view" => describe_view(forest, opt.value)?,
+            "--heatmap" => match opt.value {
+                "scenic" => print!(
+                    "{}",
+                    terminal_heatmap(&forest.scenic_scores(), Some(forest.best_spot()))
+                ),
+                "visibility" => print!("{}", terminal_heatmap(&visibility(), None)),
+                _ => return Err(opt.invalid()),
+            },
+            "--scenic-pgm" => pgms.push((forest.scenic_scores(), opt.value)),
+            "--visibility-pgm" => pgms.push((visibility(), opt.value)),
+            "--scale" => scale = opt.parse()?,
+            _ => return Err(opt.unknown()),
         }
     }
 
-    score
-}
-
-fn get_scenic_score(forest: &Forest, x: usize, y: usize, w: usize, h: usize) -> usize {
-    let mut sn = 0;
-    let mut ss = 0;
-    let mut se = 0;
-    let mut sw = 0;
-
-    let ref_height = forest[y][x].height;
-
-    for i in (0..y).rev() {
-        sn = y - i;
-        if forest[i][x].height >= ref_height {
-            break;
-        }
+    for (values, path) in pgms {
+        File::create(path)
+            .map(BufWriter::new)
+            .and_then(|mut out| write_pgm(&values, &mut out, scale))
+            .map_err(|e| format!("Unable to write {}: {}", path, e))?;
+        println!("Wrote {}", path);
     }
 
-    #[allow(clippy::needless_range_loop)]
-    for i in y + 1..h {
-        ss = i - y;
-        if forest[i][x].height >= ref_height {
-            break;
-        }
-    }
+    Ok(())
+}
 
-    for i in (0..x).rev() {
-        sw = x - i;
-        if forest[y][i].height >= ref_height {
-            break;
-        }
+/// Prints what can be seen from the tree at `X,Y`
+fn describe_view(forest: &Forest, pos: &str) -> Result<(), String> {
+    let (x, y) = pos
+        .split_once(',')
+        .and_then(|(x, y)| Some((x.parse::<usize>().ok()?, y.parse::<usize>().ok()?)))
+        .ok_or_else(|| format!("Invalid position: {}", pos))?;
+
+    if x >= forest.width() || y >= forest.height() {
+        return Err(format!(
+            "{},{} is outside of the {}x{} forest",
+            x,
+            y,
+            forest.width(),
+            forest.height()
+        ));
     }
 
-    for i in x + 1..w {
-        se = i - x;
-        if forest[y][i].height >= ref_height {
-            break;
-        }
+    let (bx, by) = forest.best_spot();
+    let view = forest.view(x, y);
+    println!("Tree at {},{} of height {}", x, y, forest.tree_height(x, y));
+    for dir in Direction::ALL {
+        println!(
+            "  {:?}: {} trees{}",
+            dir,
+            view.distance(dir),
+            if view.visibility().contains(dir) {
+                ", visible from outside"
+            } else {
+                ""
+            }
+        );
     }
-
-    sn * ss * se * sw
+    println!(
+        "  Scenic score: {} (best is {} at {},{})",
+        view.scenic_score(),
+        forest.best_scenic_score(),
+        bx,
+        by
+    );
+
+    Ok(())
 }
 
 fn parse_forest(input: &[String]) -> Forest {
     input
-        .iter()
-        .map(|s| {
-            s.chars()
-                .map(|c| Tree {
-                    height: c as i8 - '0' as i8,
-                    visible: false,
-                })
-                .collect_vec()
-        })
-        .collect_vec()
+        .join("\n")
+        .parse()
+        .unwrap_or_else(|e| panic!("Invalid forest: {}", e))
 }
 
 #[cfg(test)]
@@ -198,7 +161,7 @@ mod tests {
             .map(String::from)
             .collect_vec();
 
-        assert_eq!(get_number_of_visible_trees(&input), 21);
+        assert_eq!(parse_forest(&input).visible_count(), 21);
     }
 
     #[test]
@@ -209,7 +172,7 @@ mod tests {
             .map(String::from)
             .collect_vec();
 
-        assert_eq!(get_best_scenic_score(&input), 8);
+        assert_eq!(parse_forest(&input).best_scenic_score(), 8);
     }
 
     #[test]
@@ -222,7 +185,7 @@ mod tests {
 
         let forest = parse_forest(&input);
 
-        assert_eq!(get_scenic_score(&forest, 2, 1, 5, 5), 4);
-        assert_eq!(get_scenic_score(&forest, 2, 3, 5, 5), 8);
+        assert_eq!(forest.view(2, 1).scenic_score(), 4);
+        assert_eq!(forest.view(2, 3).scenic_score(), 8);
     }
 }
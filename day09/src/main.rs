@@ -1,10 +1,23 @@
-use std::cmp::Ordering;
-use std::collections::HashSet;
+use std::env;
 use std::fmt::Display;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter};
+use std::path::{Path, PathBuf};
 use std::time::Instant;
 
+use aoc_common::cli::options;
 use aoc_common::get_input;
 
+use crate::motion::{read_motions, steps, Motion};
+use crate::render::{render, visit_counts, visited_until, write_heatmap, Bounds, ImageFormat};
+use crate::rope::{snapshots, Rope};
+use crate::visited::VisitedSet;
+
+mod motion;
+mod render;
+mod rope;
+mod visited;
+
 fn solve(input: &[String]) -> (impl Display, impl Display) {
     let motions = parse_motions(input);
 
@@ -29,206 +42,168 @@ fn main() {
     println!("Part 1: {}", r1);
     println!("Part 2: {}", r2);
     println!("Duration: {:.3}μs", t);
-}
-
-#[derive(Debug)]
-enum Motion {
-    Up(u8),
-    Down(u8),
-    Left(u8),
-    Right(u8),
-}
-
-impl Motion {
-    fn tick_coords(&self) -> (i16, i16) {
-        match self {
-            Self::Up(_) => (0, 1),
-            Self::Down(_) => (0, -1),
-            Self::Left(_) => (-1, 0),
-            Self::Right(_) => (1, 0),
-        }
-    }
 
-    fn len(&self) -> u8 {
-        *match self {
-            Self::Up(n) => n,
-            Self::Down(n) => n,
-            Self::Left(n) => n,
-            Self::Right(n) => n,
-        }
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    if !args.is_empty() {
+        let opts = Options::from_args(&args).unwrap_or_else(|e| panic!("{}", e));
+        inspect(&parse_motions(&input), &opts).unwrap_or_else(|e| panic!("{}", e));
     }
 }
 
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
-struct Position(i16, i16);
-
-impl Position {
-    fn tick(&mut self, x: i16, y: i16) {
-        self.0 += x;
-        self.1 += y;
-    }
+struct Options {
+    knots: usize,
+    render: Option<Option<usize>>,
+    heatmap: Option<PathBuf>,
+    scale: usize,
+    stream: Option<PathBuf>,
 }
 
-struct Boundaries {
-    u: i16,
-    d: i16,
-    l: i16,
-    r: i16,
-}
-
-impl Boundaries {
-    fn update(&mut self, pos: &Position) {
-        if pos.0 > self.r {
-            self.r = pos.0;
-        } else if pos.0 < self.l {
-            self.l = pos.0;
+impl Options {
+    /// Parses `[--knots N] [--render STEP|last] [--heatmap FILE.pgm|FILE.png] [--scale N]
+    /// [--stream FILE|-]`, where `--stream` counts the cells visited by a 2-knot rope and a rope of
+    /// `--knots` knots, reading the motions from a file or stdin instead of the puzzle input.
+    fn from_args(args: &[String]) -> Result<Self, String> {
+        let mut opts = Options {
+            knots: 10,
+            render: None,
+            heatmap: None,
+            scale: 4,
+            stream: None,
+        };
+
+        for opt in options(args) {
+            let opt = opt?;
+
+            match opt.name {
+                "--knots" => {
+                    opts.knots = opt.parse()?;
+                    if opts.knots == 0 {
+                        return Err(opt.invalid());
+                    }
+                }
+                "--render" => {
+                    opts.render = match opt.value {
+                        "last" => Some(None),
+                        v => Some(Some(v.parse().map_err(|_| opt.invalid())?)),
+                    };
+                }
+                "--heatmap" => opts.heatmap = Some(PathBuf::from(opt.value)),
+                "--scale" => opts.scale = opt.parse()?,
+                "--stream" => opts.stream = Some(PathBuf::from(opt.value)),
+                _ => return Err(opt.unknown()),
+            }
         }
 
-        if pos.1 > self.u {
-            self.u = pos.1;
-        } else if pos.1 < self.d {
-            self.d = pos.1;
-        }
+        Ok(opts)
     }
 }
 
-impl From<&str> for Motion {
-    fn from(s: &str) -> Self {
-        let (dir, count) = s.split_once(' ').unwrap();
-        let count = count.parse().unwrap();
-
-        match dir {
-            "U" => Motion::Up(count),
-            "D" => Motion::Down(count),
-            "L" => Motion::Left(count),
-            "R" => Motion::Right(count),
-            _ => panic!("Invalid direction: {}", dir),
+fn inspect(motions: &[Motion], opts: &Options) -> io::Result<()> {
+    if let Some(path) = &opts.stream {
+        let reader: Box<dyn BufRead> = if path.as_os_str() == "-" {
+            Box::new(io::stdin().lock())
+        } else {
+            Box::new(BufReader::new(File::open(path)?))
+        };
+
+        let tails = [1, opts.knots - 1];
+        let counts = count_streamed(reader, &tails).map_err(io::Error::other)?;
+        for (n, count) in tails.iter().zip(counts) {
+            println!("{} knots: {}", n + 1, count);
         }
     }
-}
-
-fn get_visited_positions(motions: &Vec<Motion>, knots: usize) -> usize {
-    let mut head = Position(0, 0);
-    let mut tails = Vec::with_capacity(knots);
-    let mut visited = HashSet::new();
 
-    for _ in 0..knots {
-        tails.push(Position(0, 0));
+    if opts.render.is_none() && opts.heatmap.is_none() {
+        return Ok(());
     }
 
-    let mut boundaries = Boundaries {
-        l: 0,
-        d: 0,
-        r: 5,
-        u: 5,
-    };
-
-    #[cfg(test)]
-    println!("== Initial State ==\n");
-
-    render(&boundaries, &head, &tails, &visited);
-
-    visited.insert(tails[knots - 1].clone());
-
-    for motion in motions {
-        #[cfg(test)]
-        println!("== {:?} ==\n", motion);
-
-        let tick_coords = motion.tick_coords();
-
-        for _ in 0..motion.len() {
-            head.tick(tick_coords.0, tick_coords.1);
+    let snapshots = snapshots(opts.knots, steps(motions.iter().copied()));
 
-            let m = get_move(&head, &tails[0]);
-            tails[0].0 += m.0;
-            tails[0].1 += m.1;
+    if let Some(step) = opts.render {
+        let step = step.unwrap_or(snapshots.len() - 1);
+        let knots = snapshots.get(step).ok_or_else(|| {
+            io::Error::other(format!("Only {} steps to render", snapshots.len() - 1))
+        })?;
 
-            for i in 1..knots {
-                let m = get_move(&tails[i - 1], &tails[i]);
-                tails[i].0 += m.0;
-                tails[i].1 += m.1;
-            }
-
-            visited.insert(tails[knots - 1].clone());
-
-            boundaries.update(&head);
-
-            render(&boundaries, &head, &tails, &visited);
-        }
+        let bounds = Bounds::covering(snapshots.iter().flatten());
+        println!("== Step {} ==", step);
+        print!(
+            "{}",
+            render(knots, &visited_until(&snapshots, step), &bounds)
+        );
     }
 
-    visited.len()
-}
+    if let Some(path) = &opts.heatmap {
+        let format = match path.extension().and_then(|e| e.to_str()) {
+            Some("png") => ImageFormat::Png,
+            _ => ImageFormat::Pgm,
+        };
 
-fn get_move(head: &Position, tail: &Position) -> (i16, i16) {
-    if (head.0 - tail.0).abs() <= 1 && (head.1 - tail.1).abs() <= 1 {
-        return (0, 0);
+        let mut out = BufWriter::new(File::create(path)?);
+        write_heatmap(&visit_counts(&snapshots), &mut out, format, opts.scale)?;
+        println!("Wrote heatmap to {}", Path::new(path).display());
     }
 
-    let x = match head.0.cmp(&tail.0) {
-        Ordering::Greater => 1,
-        Ordering::Less => -1,
-        Ordering::Equal => 0,
-    };
+    Ok(())
+}
 
-    let y = match head.1.cmp(&tail.1) {
-        Ordering::Greater => 1,
-        Ordering::Less => -1,
-        Ordering::Equal => 0,
-    };
+/// Number of cells visited by the tail of ropes with each given number of knots after the head,
+/// simulating all of them in a single pass over the motions
+fn count_visited<I>(motions: I, tails: &[usize]) -> Vec<usize>
+where
+    I: IntoIterator<Item = Motion>,
+{
+    let mut ropes = tails
+        .iter()
+        .map(|&n| (Rope::new(n + 1), VisitedSet::default()))
+        .collect::<Vec<_>>();
 
-    (x, y)
-}
+    for (rope, visited) in ropes.iter_mut() {
+        visited.insert(rope.tail());
+    }
 
-#[cfg(test)]
-fn render(
-    boundaries: &Boundaries,
-    head: &Position,
-    tails: &[Position],
-    visited: &HashSet<Position>,
-) {
-    for y in (boundaries.d..=boundaries.u).rev() {
-        for x in boundaries.l..=boundaries.r {
-            let cur = Position(x, y);
-
-            let knot = tails.iter().position(|k| k == &cur);
-
-            if &cur == head {
-                print!("H");
-            } else if knot.is_some() {
-                print!("{}", knot.unwrap() + 1);
-            } else if cur.0 == 0 && cur.1 == 0 {
-                print!("s");
-            } else if visited.contains(&cur) {
-                print!("#");
-            } else {
-                print!(".");
-            }
+    for (dx, dy) in steps(motions) {
+        for (rope, visited) in ropes.iter_mut() {
+            visited.insert(rope.step(dx, dy));
         }
-
-        println!();
     }
-    println!();
+
+    ropes.iter().map(|(_, visited)| visited.len()).collect()
 }
 
-#[cfg(not(test))]
-fn render(
-    _boundaries: &Boundaries,
-    _head: &Position,
-    _tails: &[Position],
-    _visited: &HashSet<Position>,
-) {
+fn get_visited_positions(motions: &[Motion], knots: usize) -> usize {
+    count_visited(motions.iter().copied(), &[knots])[0]
 }
 
 fn parse_motions(input: &[String]) -> Vec<Motion> {
     input
         .iter()
-        .map(|s| s.as_str().into())
-        .collect::<Vec<Motion>>()
+        .enumerate()
+        .filter(|(_, s)| !s.is_empty())
+        .map(|(idx, s)| {
+            s.parse()
+                .unwrap_or_else(|e| panic!("Invalid motion on line {}: {}", idx + 1, e))
+        })
+        .collect()
+}
+
+/// Counts the visited cells for motions read from `reader`, stopping at the first invalid line
+fn count_streamed<R: BufRead>(reader: R, tails: &[usize]) -> Result<Vec<usize>, String> {
+    let mut error = None;
+    let motions = read_motions(reader).map_while(|m| m.map_err(|e| error = Some(e)).ok());
+
+    let counts = count_visited(motions, tails);
+
+    match error {
+        Some(e) => Err(e),
+        None => Ok(counts),
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::io::Cursor;
+
     use itertools::Itertools;
 
     use super::*;
@@ -288,4 +263,28 @@ U 20"
 
         assert_eq!(get_visited_positions(&motions, 9), 36);
     }
+
+    #[test]
+    fn test_streamed() {
+        // Counts that don't fit in a byte
+        let input = format!("{}R 1000\nU 300\nL 2000\n", TEST_INPUT);
+        let motions = parse_motions(&input.lines().map(String::from).collect_vec());
+
+        let expected = [2, 10].map(|n| {
+            let snapshots = snapshots(n, steps(motions.iter().copied()));
+            visited_until(&snapshots, snapshots.len() - 1).len()
+        });
+
+        assert_eq!(
+            count_streamed(Cursor::new(input), &[1, 9]),
+            Ok(expected.to_vec())
+        );
+        assert!(expected[1] > 2000);
+
+        let reader = Cursor::new("R 4\nU 4\nL 3\nD x\n");
+        assert_eq!(
+            count_streamed(reader, &[1]),
+            Err(String::from("Line 4: Invalid count: 'x'"))
+        );
+    }
 }
@@ -0,0 +1,123 @@
+use std::io::BufRead;
+use std::str::FromStr;
+
+use crate::rope::Coord;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Motion {
+    Up(u64),
+    Down(u64),
+    Left(u64),
+    Right(u64),
+}
+
+impl Motion {
+    pub fn tick_coords(&self) -> (Coord, Coord) {
+        match self {
+            Self::Up(_) => (0, 1),
+            Self::Down(_) => (0, -1),
+            Self::Left(_) => (-1, 0),
+            Self::Right(_) => (1, 0),
+        }
+    }
+
+    pub fn len(&self) -> u64 {
+        *match self {
+            Self::Up(n) => n,
+            Self::Down(n) => n,
+            Self::Left(n) => n,
+            Self::Right(n) => n,
+        }
+    }
+}
+
+impl FromStr for Motion {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (dir, count) = s
+            .trim()
+            .split_once(' ')
+            .ok_or_else(|| format!("Expected a direction and a count, got '{}'", s))?;
+        let count = count
+            .parse()
+            .map_err(|_| format!("Invalid count: '{}'", count))?;
+
+        match dir {
+            "U" => Ok(Motion::Up(count)),
+            "D" => Ok(Motion::Down(count)),
+            "L" => Ok(Motion::Left(count)),
+            "R" => Ok(Motion::Right(count)),
+            _ => Err(format!("Invalid direction: '{}'", dir)),
+        }
+    }
+}
+
+/// Reads one motion per non-empty line as the reader goes, so inputs of any size can be
+/// processed without loading them
+pub fn read_motions<R: BufRead>(reader: R) -> impl Iterator<Item = Result<Motion, String>> {
+    reader
+        .lines()
+        .enumerate()
+        .filter_map(|(idx, line)| match line {
+            Ok(l) if l.trim().is_empty() => None,
+            Ok(l) => Some(l.parse().map_err(|e| format!("Line {}: {}", idx + 1, e))),
+            Err(e) => Some(Err(format!("Line {}: {}", idx + 1, e))),
+        })
+}
+
+/// Unit moves of the head
+pub fn steps<I>(motions: I) -> impl Iterator<Item = (Coord, Coord)>
+where
+    I: IntoIterator<Item = Motion>,
+{
+    motions.into_iter().flat_map(|m| {
+        let delta = m.tick_coords();
+        (0..m.len()).map(move |_| delta)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::*;
+
+    #[test]
+    fn test_read_motions() {
+        let motions = read_motions(Cursor::new("R 4\n\nU 1000\nL 18446744073709551615\n"))
+            .collect::<Result<Vec<_>, _>>();
+
+        assert_eq!(
+            motions,
+            Ok(vec![
+                Motion::Right(4),
+                Motion::Up(1000),
+                Motion::Left(u64::MAX)
+            ])
+        );
+    }
+
+    #[test]
+    fn test_read_errors() {
+        let errors = read_motions(Cursor::new("R 4\nX 1\nU -2\nD\n"))
+            .filter_map(Result::err)
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            errors,
+            vec![
+                "Line 2: Invalid direction: 'X'",
+                "Line 3: Invalid count: '-2'",
+                "Line 4: Expected a direction and a count, got 'D'"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_steps() {
+        let steps = steps([Motion::Right(2), Motion::Down(0), Motion::Up(1)]).collect::<Vec<_>>();
+
+        assert_eq!(steps, vec![(1, 0), (1, 0), (0, 1)]);
+    }
+}
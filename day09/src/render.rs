@@ -0,0 +1,235 @@
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+use crate::rope::Pos;
+use crate::visited::VisitedSet;
+
+/// Area to draw, always including the origin
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Bounds {
+    pub min: Pos,
+    pub max: Pos,
+}
+
+impl Bounds {
+    pub fn covering<'a, I: IntoIterator<Item = &'a Pos>>(positions: I) -> Self {
+        positions.into_iter().fold(
+            Bounds {
+                min: Pos::default(),
+                max: Pos::default(),
+            },
+            |b, p| Bounds {
+                min: Pos::new(b.min.x.min(p.x), b.min.y.min(p.y)),
+                max: Pos::new(b.max.x.max(p.x), b.max.y.max(p.y)),
+            },
+        )
+    }
+
+    fn width(&self) -> usize {
+        (self.max.x - self.min.x + 1) as usize
+    }
+
+    fn height(&self) -> usize {
+        (self.max.y - self.min.y + 1) as usize
+    }
+
+    /// Cells from the top left corner, row by row, with `y` growing upwards like in the puzzle
+    fn rows(&self) -> impl Iterator<Item = impl Iterator<Item = Pos>> {
+        let (min_x, max_x) = (self.min.x, self.max.x);
+
+        (self.min.y..=self.max.y)
+            .rev()
+            .map(move |y| (min_x..=max_x).map(move |x| Pos::new(x, y)))
+    }
+}
+
+/// Draws the rope the way the puzzle does, with the cells visited by the tail shown as `#`.
+/// The knots of a two-knot rope are `H` and `T`, the others are numbered from the head.
+pub fn render(knots: &[Pos], visited: &VisitedSet, bounds: &Bounds) -> String {
+    let mut out = String::new();
+
+    for row in bounds.rows() {
+        for p in row {
+            let c = match knots.iter().position(|k| *k == p) {
+                Some(0) => 'H',
+                Some(1) if knots.len() == 2 => 'T',
+                Some(i) => char::from_digit(i as u32 % 36, 36).unwrap(),
+                None if p == Pos::default() => 's',
+                None if visited.contains(p) => '#',
+                None => '.',
+            };
+            out.push(c);
+        }
+        out.push('\n');
+    }
+
+    out
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Pgm,
+    Png,
+}
+
+/// Number of times the tail was on each cell, over all the snapshots
+pub fn visit_counts(snapshots: &[Vec<Pos>]) -> HashMap<Pos, u32> {
+    let mut counts = HashMap::new();
+
+    for knots in snapshots {
+        *counts.entry(knots[knots.len() - 1]).or_insert(0) += 1;
+    }
+
+    counts
+}
+
+/// Grayscale image of the visit counts, black for cells never visited and brighter the more
+/// often the tail went through. Each cell is scaled to a `scale` x `scale` square.
+pub fn write_heatmap<W: Write>(
+    counts: &HashMap<Pos, u32>,
+    out: &mut W,
+    format: ImageFormat,
+    scale: usize,
+) -> io::Result<()> {
+    let scale = scale.max(1);
+    let bounds = Bounds::covering(counts.keys());
+    let (w, h) = (bounds.width() * scale, bounds.height() * scale);
+    let max = counts.values().copied().max().unwrap_or(1) as u64;
+
+    let row_bytes = bounds.rows().map(|row| {
+        row.map(|p| match counts.get(&p) {
+            Some(&n) => (64 + 191 * n as u64 / max) as u8,
+            None => 0,
+        })
+        .flat_map(|v| std::iter::repeat_n(v, scale))
+        .collect::<Vec<u8>>()
+    });
+    let data = row_bytes
+        .flat_map(|row| std::iter::repeat_n(row, scale))
+        .flatten()
+        .collect::<Vec<u8>>();
+
+    match format {
+        ImageFormat::Pgm => {
+            write!(out, "P5\n{} {}\n255\n", w, h)?;
+            out.write_all(&data)
+        }
+        ImageFormat::Png => {
+            let mut encoder = png::Encoder::new(out, w as u32, h as u32);
+            encoder.set_color(png::ColorType::Grayscale);
+            encoder.set_depth(png::BitDepth::Eight);
+
+            encoder
+                .write_header()
+                .and_then(|mut writer| writer.write_image_data(&data))
+                .map_err(io::Error::other)
+        }
+    }
+}
+
+/// Cells visited by the tail up to a given snapshot
+pub fn visited_until(snapshots: &[Vec<Pos>], step: usize) -> VisitedSet {
+    let mut visited = VisitedSet::default();
+
+    for knots in &snapshots[..=step] {
+        visited.insert(knots[knots.len() - 1]);
+    }
+
+    visited
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rope::snapshots;
+
+    /// Unit steps of `R 4, U 4, L 3, D 1, R 4, D 1, L 5, R 2`
+    fn steps() -> Vec<(i64, i64)> {
+        [(1, 0, 4), (0, 1, 4), (-1, 0, 3), (0, -1, 1)]
+            .into_iter()
+            .chain([(1, 0, 4), (0, -1, 1), (-1, 0, 5), (1, 0, 2)])
+            .flat_map(|(dx, dy, n)| std::iter::repeat_n((dx, dy), n))
+            .collect()
+    }
+
+    #[test]
+    fn test_render_step() {
+        let snapshots = snapshots(2, steps());
+        let bounds = Bounds::covering(snapshots.iter().flatten());
+
+        assert_eq!(
+            render(&snapshots[4], &visited_until(&snapshots, 4), &bounds),
+            "......
+......
+......
+......
+s##TH.
+"
+        );
+        assert_eq!(
+            render(&snapshots[8], &visited_until(&snapshots, 8), &bounds),
+            "....H.
+....T.
+....#.
+....#.
+s###..
+"
+        );
+    }
+
+    #[test]
+    fn test_render_visited() {
+        let snapshots = snapshots(2, steps());
+        let bounds = Bounds::covering(snapshots.iter().flatten());
+        let visited = visited_until(&snapshots, snapshots.len() - 1);
+
+        assert_eq!(visited.len(), 13);
+        assert_eq!(
+            render(&[], &visited, &bounds),
+            "..##..
+...##.
+.####.
+....#.
+s###..
+"
+        );
+    }
+
+    #[test]
+    fn test_render_numbered_knots() {
+        let snapshots = snapshots(10, steps());
+        let bounds = Bounds::covering(snapshots.iter().flatten());
+
+        assert_eq!(
+            render(&snapshots[8], &VisitedSet::default(), &bounds),
+            "....H.
+....1.
+..432.
+.5....
+6.....
+"
+        );
+    }
+
+    #[test]
+    fn test_heatmap() {
+        let counts = visit_counts(&snapshots(2, steps()));
+        assert_eq!(counts.values().sum::<u32>(), 25);
+
+        let mut pgm = Vec::new();
+        write_heatmap(&counts, &mut pgm, ImageFormat::Pgm, 2).unwrap();
+
+        let header = b"P5\n10 10\n255\n";
+        assert!(pgm.starts_with(header));
+        assert_eq!(pgm.len(), header.len() + 10 * 10);
+        assert_eq!(pgm[header.len()], 0);
+        assert_eq!(
+            pgm.iter().skip(header.len()).filter(|&&v| v > 0).count(),
+            13 * 4
+        );
+
+        let mut png = Vec::new();
+        write_heatmap(&counts, &mut png, ImageFormat::Png, 1).unwrap();
+        assert!(png.starts_with(b"\x89PNG"));
+    }
+}
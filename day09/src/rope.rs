@@ -0,0 +1,120 @@
+pub type Coord = i64;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Pos {
+    pub x: Coord,
+    pub y: Coord,
+}
+
+impl Pos {
+    pub fn new(x: Coord, y: Coord) -> Self {
+        Pos { x, y }
+    }
+
+    /// Where this knot goes when the knot ahead of it is at `leader`
+    fn follow(self, leader: Pos) -> Pos {
+        let (dx, dy) = (leader.x - self.x, leader.y - self.y);
+
+        if dx.abs() <= 1 && dy.abs() <= 1 {
+            return self;
+        }
+
+        Pos::new(self.x + dx.signum(), self.y + dy.signum())
+    }
+}
+
+/// A rope made of any number of knots, the first one being the head
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rope {
+    knots: Vec<Pos>,
+}
+
+impl Rope {
+    /// A rope of `len` knots, head included, all starting at the origin
+    pub fn new(len: usize) -> Self {
+        assert!(len > 0, "A rope needs at least one knot");
+
+        Rope {
+            knots: vec![Pos::default(); len],
+        }
+    }
+
+    pub fn knots(&self) -> &[Pos] {
+        &self.knots
+    }
+
+    pub fn tail(&self) -> Pos {
+        self.knots[self.knots.len() - 1]
+    }
+
+    /// Moves the head by `(dx, dy)` and lets every other knot follow, returning the new tail
+    pub fn step(&mut self, dx: Coord, dy: Coord) -> Pos {
+        self.knots[0] = Pos::new(self.knots[0].x + dx, self.knots[0].y + dy);
+
+        for i in 1..self.knots.len() {
+            let next = self.knots[i].follow(self.knots[i - 1]);
+            if next == self.knots[i] {
+                // The rest of the rope can't move either
+                break;
+            }
+            self.knots[i] = next;
+        }
+
+        self.tail()
+    }
+}
+
+/// Positions of every knot before the first step and after each step
+pub fn snapshots<I>(len: usize, steps: I) -> Vec<Vec<Pos>>
+where
+    I: IntoIterator<Item = (Coord, Coord)>,
+{
+    let mut rope = Rope::new(len);
+    let mut snapshots = vec![rope.knots().to_vec()];
+
+    for (dx, dy) in steps {
+        rope.step(dx, dy);
+        snapshots.push(rope.knots().to_vec());
+    }
+
+    snapshots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_follow() {
+        let tail = Pos::new(1, 1);
+
+        assert_eq!(tail.follow(Pos::new(2, 2)), tail);
+        assert_eq!(tail.follow(Pos::new(3, 1)), Pos::new(2, 1));
+        assert_eq!(tail.follow(Pos::new(2, 3)), Pos::new(2, 2));
+        assert_eq!(tail.follow(Pos::new(-1, -1)), Pos::new(0, 0));
+    }
+
+    #[test]
+    fn test_step() {
+        let mut rope = Rope::new(3);
+
+        assert_eq!(rope.step(1, 0), Pos::new(0, 0));
+        assert_eq!(rope.step(1, 0), Pos::new(0, 0));
+        assert_eq!(rope.step(1, 0), Pos::new(1, 0));
+        assert_eq!(rope.step(0, 1), Pos::new(1, 0));
+        assert_eq!(rope.step(0, 1), Pos::new(2, 1));
+        assert_eq!(
+            rope.knots(),
+            &[Pos::new(3, 2), Pos::new(3, 1), Pos::new(2, 1)]
+        );
+    }
+
+    #[test]
+    fn test_long_motion() {
+        // Well past what 16-bit coordinates can hold
+        let mut rope = Rope::new(10);
+        let tail = (0..100_000).map(|_| rope.step(1, 0)).last();
+
+        assert_eq!(tail, Some(Pos::new(99_991, 0)));
+    }
+}
@@ -0,0 +1,71 @@
+use rustc_hash::FxHashMap;
+
+use crate::rope::{Coord, Pos};
+
+/// Side of the square tiles the set is made of, as a power of two
+const TILE_BITS: u32 = 6;
+const TILE_SIZE: Coord = 1 << TILE_BITS;
+
+/// Set of positions stored as 64x64 bitmaps, allocated only for the tiles that were visited.
+/// A rope mostly moves locally, so this takes a fraction of the memory of a `HashSet<Pos>`.
+#[derive(Debug, Default)]
+pub struct VisitedSet {
+    tiles: FxHashMap<(Coord, Coord), Box<[u64; TILE_SIZE as usize]>>,
+    len: usize,
+}
+
+impl VisitedSet {
+    /// Adds a position, returning whether it wasn't already there
+    pub fn insert(&mut self, p: Pos) -> bool {
+        let tile = self
+            .tiles
+            .entry((p.x >> TILE_BITS, p.y >> TILE_BITS))
+            .or_insert_with(|| Box::new([0; TILE_SIZE as usize]));
+
+        let row = &mut tile[(p.y & (TILE_SIZE - 1)) as usize];
+        let bit = 1 << (p.x & (TILE_SIZE - 1));
+        let new = *row & bit == 0;
+
+        *row |= bit;
+        self.len += new as usize;
+
+        new
+    }
+
+    pub fn contains(&self, p: Pos) -> bool {
+        self.tiles
+            .get(&(p.x >> TILE_BITS, p.y >> TILE_BITS))
+            .is_some_and(|t| {
+                t[(p.y & (TILE_SIZE - 1)) as usize] & (1 << (p.x & (TILE_SIZE - 1))) != 0
+            })
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::*;
+
+    #[test]
+    fn test_matches_hash_set() {
+        let mut set = VisitedSet::default();
+        let mut expected = HashSet::new();
+
+        // Positions spread over several tiles on both sides of the origin
+        let positions = (0..5000i64).map(|i| Pos::new((i % 157) - 80, (i * 7 % 131) - 65));
+
+        for p in positions {
+            assert_eq!(set.insert(p), expected.insert(p));
+        }
+
+        assert_eq!(set.len(), expected.len());
+        assert!(expected.iter().all(|&p| set.contains(p)));
+        assert!(!set.contains(Pos::new(-1000, 1000)));
+        assert!(!set.contains(Pos::new(64, 200)));
+    }
+}
@@ -0,0 +1,159 @@
+use std::io::{self, Write};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Screen {
+    width: usize,
+    height: usize,
+    pixels: Vec<bool>,
+}
+
+impl Screen {
+    pub fn new(width: usize, height: usize) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![false; width * height],
+        }
+    }
+
+    pub fn is_lit(&self, x: usize, y: usize) -> bool {
+        x < self.width && y < self.height && self.pixels[y * self.width + x]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, lit: bool) {
+        self.pixels[y * self.width + x] = lit;
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    /// One character per pixel
+    Ascii { lit: char, dark: char },
+    /// Two rows per line using the `▀`, `▄` and `█` characters
+    HalfBlock,
+    /// Two colored cells per pixel, for terminals supporting ANSI escapes
+    Ansi,
+    /// Plain (P1) portable bitmap, each pixel scaled to a `scale` x `scale` square
+    Pbm { scale: usize },
+    /// Grayscale PNG, each pixel scaled to a `scale` x `scale` square
+    Png { scale: usize },
+}
+
+impl Default for Backend {
+    fn default() -> Self {
+        Backend::Ascii {
+            lit: '#',
+            dark: '.',
+        }
+    }
+}
+
+impl Backend {
+    pub fn render<W: Write>(&self, screen: &Screen, out: &mut W) -> io::Result<()> {
+        match *self {
+            Backend::Ascii { lit, dark } => render_ascii(screen, out, lit, dark),
+            Backend::HalfBlock => render_half_block(screen, out),
+            Backend::Ansi => render_ansi(screen, out),
+            Backend::Pbm { scale } => render_pbm(screen, out, scale),
+            Backend::Png { scale } => render_png(screen, out, scale),
+        }
+    }
+
+    pub fn render_to_string(&self, screen: &Screen) -> String {
+        let mut buf = Vec::new();
+        self.render(screen, &mut buf)
+            .expect("Writing to a Vec should not fail");
+
+        String::from_utf8_lossy(&buf).into_owned()
+    }
+}
+
+fn render_ascii<W: Write>(screen: &Screen, out: &mut W, lit: char, dark: char) -> io::Result<()> {
+    for y in 0..screen.height {
+        let line: String = (0..screen.width)
+            .map(|x| if screen.is_lit(x, y) { lit } else { dark })
+            .collect();
+        writeln!(out, "{}", line)?;
+    }
+
+    Ok(())
+}
+
+fn render_half_block<W: Write>(screen: &Screen, out: &mut W) -> io::Result<()> {
+    for y in (0..screen.height).step_by(2) {
+        let line: String = (0..screen.width)
+            .map(|x| match (screen.is_lit(x, y), screen.is_lit(x, y + 1)) {
+                (true, true) => '█',
+                (true, false) => '▀',
+                (false, true) => '▄',
+                (false, false) => ' ',
+            })
+            .collect();
+        writeln!(out, "{}", line)?;
+    }
+
+    Ok(())
+}
+
+fn render_ansi<W: Write>(screen: &Screen, out: &mut W) -> io::Result<()> {
+    for y in 0..screen.height {
+        for x in 0..screen.width {
+            if screen.is_lit(x, y) {
+                write!(out, "\x1b[102m  ")?;
+            } else {
+                write!(out, "\x1b[40m  ")?;
+            }
+        }
+        writeln!(out, "\x1b[0m")?;
+    }
+
+    Ok(())
+}
+
+fn render_pbm<W: Write>(screen: &Screen, out: &mut W, scale: usize) -> io::Result<()> {
+    let scale = scale.max(1);
+
+    writeln!(out, "P1")?;
+    writeln!(out, "{} {}", screen.width * scale, screen.height * scale)?;
+
+    for y in 0..screen.height * scale {
+        let line = (0..screen.width * scale)
+            .map(|x| {
+                if screen.is_lit(x / scale, y / scale) {
+                    "1"
+                } else {
+                    "0"
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+        writeln!(out, "{}", line)?;
+    }
+
+    Ok(())
+}
+
+fn render_png<W: Write>(screen: &Screen, out: &mut W, scale: usize) -> io::Result<()> {
+    let scale = scale.max(1);
+    let (w, h) = (screen.width * scale, screen.height * scale);
+
+    let mut encoder = png::Encoder::new(out, w as u32, h as u32);
+    encoder.set_color(png::ColorType::Grayscale);
+    encoder.set_depth(png::BitDepth::Eight);
+
+    let data = (0..h)
+        .flat_map(|y| (0..w).map(move |x| (x, y)))
+        .map(|(x, y)| {
+            if screen.is_lit(x / scale, y / scale) {
+                0xff
+            } else {
+                0x00
+            }
+        })
+        .collect::<Vec<u8>>();
+
+    encoder
+        .write_header()
+        .and_then(|mut writer| writer.write_image_data(&data))
+        .map_err(io::Error::other)
+}
@@ -1,10 +1,17 @@
+use std::env;
 use std::fmt::Display;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
 use std::str::FromStr;
 use std::time::Instant;
 
 use aoc_common::get_input;
 use itertools::Itertools;
 
+use crate::crt::{Backend, Screen};
+
+mod crt;
+
 fn main() {
     let input = get_input("day10.txt");
 
@@ -17,6 +24,94 @@ fn main() {
     println!("Part 1: {}", r1);
     println!("Part 2: \n{}", r2);
     println!("Duration: {:.3}μs", t);
+
+    let args = env::args().skip(1).collect_vec();
+    if !args.is_empty() {
+        let opts = RenderOptions::from_args(&args).unwrap_or_else(|e| panic!("{}", e));
+        let screen = render_screen(
+            &parse_instructions(&input),
+            opts.width,
+            opts.height,
+            opts.sprite_width,
+        );
+
+        let res = match &opts.output {
+            Some(path) => {
+                let mut out = BufWriter::new(File::create(path).expect("Unable to create output"));
+                opts.backend
+                    .render(&screen, &mut out)
+                    .and_then(|_| out.flush())
+            }
+            None => opts.backend.render(&screen, &mut io::stdout().lock()),
+        };
+        res.expect("Unable to render screen");
+    }
+}
+
+struct RenderOptions {
+    backend: Backend,
+    width: usize,
+    height: usize,
+    sprite_width: usize,
+    output: Option<String>,
+}
+
+impl RenderOptions {
+    /// Parses `[ascii|halfblock|ansi|pbm|png] [--size WxH] [--sprite-width N] [--scale N] [-o FILE]`
+    fn from_args(args: &[String]) -> Result<Self, String> {
+        let mut backend = "ascii";
+        let mut opts = RenderOptions {
+            backend: Backend::default(),
+            width: 40,
+            height: 6,
+            sprite_width: 3,
+            output: None,
+        };
+        let mut scale = 1;
+
+        let mut it = args.iter();
+        while let Some(arg) = it.next() {
+            let mut value = || it.next().ok_or(format!("Missing value for {}", arg));
+
+            match arg.as_str() {
+                "--size" => {
+                    let v = value()?;
+                    let (w, h) = v
+                        .split_once('x')
+                        .ok_or_else(|| format!("Invalid size: {}", v))?;
+                    opts.width = w.parse().map_err(|_| format!("Invalid width: {}", w))?;
+                    opts.height = h.parse().map_err(|_| format!("Invalid height: {}", h))?;
+                }
+                "--sprite-width" => {
+                    let v = value()?;
+                    opts.sprite_width = v
+                        .parse()
+                        .map_err(|_| format!("Invalid sprite width: {}", v))?;
+                }
+                "--scale" => {
+                    let v = value()?;
+                    scale = v.parse().map_err(|_| format!("Invalid scale: {}", v))?;
+                }
+                "-o" | "--output" => opts.output = Some(value()?.clone()),
+                b => backend = b,
+            }
+        }
+
+        opts.backend = match backend {
+            "ascii" => Backend::default(),
+            "halfblock" => Backend::HalfBlock,
+            "ansi" => Backend::Ansi,
+            "pbm" => Backend::Pbm { scale },
+            "png" => Backend::Png { scale },
+            b => return Err(format!("Invalid backend: {}", b)),
+        };
+
+        if matches!(opts.backend, Backend::Png { .. }) && opts.output.is_none() {
+            return Err(String::from("The png backend requires an output file"));
+        }
+
+        Ok(opts)
+    }
 }
 
 fn solve(input: &[String]) -> (impl Display, impl Display) {
@@ -28,7 +123,7 @@ fn solve(input: &[String]) -> (impl Display, impl Display) {
     (p1, p2)
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 enum Instruction {
     Noop,
     AddX(i8),
@@ -98,27 +193,30 @@ fn get_sum_of_signal_strength(instructions: &[Instruction], cycles: &[u32]) -> i
     sum
 }
 
-fn render_sprites(instructions: &[Instruction], w: u8, h: u8) -> String {
-    let mut x = 1i32;
+fn render_sprites(instructions: &[Instruction], w: usize, h: usize) -> String {
+    let screen = render_screen(instructions, w, h, 3);
+
+    Backend::default().render_to_string(&screen)
+}
+
+fn render_screen(instructions: &[Instruction], w: usize, h: usize, sprite_width: usize) -> Screen {
+    let mut screen = Screen::new(w, h);
+    let mut x = 1i64;
+
+    // The sprite is centered on x, leaning right when its width is even
+    let sprite_left = (sprite_width as i64 - 1) / 2;
+    let sprite_right = sprite_width as i64 / 2;
 
     let mut instr_iter = instructions.iter().cycle();
 
     let mut instr = instr_iter.next().unwrap();
     let mut instr_cycles = instr.n_cycles();
 
-    let mut chars = Vec::with_capacity((w as usize + 1) * h as usize);
-
     for cycle in 0..(w * h) {
-        let pos = cycle % w;
+        let pos = (cycle % w) as i64;
 
-        if cycle > 0 && pos == 0 {
-            chars.push('\n');
-        }
-
-        if (x - pos as i32).abs() <= 1 {
-            chars.push('#');
-        } else {
-            chars.push('.');
+        if pos >= x - sprite_left && pos <= x + sprite_right {
+            screen.set(cycle % w, cycle / w, true);
         }
 
         instr_cycles -= 1;
@@ -126,16 +224,15 @@ fn render_sprites(instructions: &[Instruction], w: u8, h: u8) -> String {
             #[cfg(test)]
             println!("Cycle={}, Instr={:?}", cycle, instr);
             if let Instruction::AddX(n) = instr {
-                x += *n as i32;
+                x += *n as i64;
             }
 
             instr = instr_iter.next().unwrap();
             instr_cycles = instr.n_cycles();
         }
     }
-    chars.push('\n');
 
-    chars.iter().collect()
+    screen
 }
 
 #[cfg(test)]
@@ -302,7 +399,7 @@ noop";
         let instructions = parse_instructions(&input);
 
         assert_eq!(
-            get_sum_of_signal_strength(&instructions, &vec![20, 60, 100, 140, 180, 220]),
+            get_sum_of_signal_strength(&instructions, &[20, 60, 100, 140, 180, 220]),
             13140
         );
     }
@@ -328,4 +425,39 @@ noop";
 
         assert_eq!(res, expected);
     }
+
+    #[test]
+    fn test_sprite_width() {
+        let instructions = vec![Instruction::Noop; 4];
+
+        let narrow = render_screen(&instructions, 4, 1, 1);
+        let wide = render_screen(&instructions, 4, 1, 4);
+
+        assert_eq!(Backend::default().render_to_string(&narrow), ".#..\n");
+        assert_eq!(Backend::default().render_to_string(&wide), "####\n");
+    }
+
+    #[test]
+    fn test_backends() {
+        let instructions = vec![Instruction::Noop; 9];
+        let screen = render_screen(&instructions, 3, 3, 3);
+
+        assert_eq!(
+            Backend::Ascii {
+                lit: '█',
+                dark: ' '
+            }
+            .render_to_string(&screen),
+            "███\n███\n███\n"
+        );
+        assert_eq!(Backend::HalfBlock.render_to_string(&screen), "███\n▀▀▀\n");
+        assert_eq!(
+            Backend::Pbm { scale: 1 }.render_to_string(&screen),
+            "P1\n3 3\n1 1 1\n1 1 1\n1 1 1\n"
+        );
+
+        let mut png = Vec::new();
+        Backend::Png { scale: 2 }.render(&screen, &mut png).unwrap();
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+    }
 }
@@ -0,0 +1,329 @@
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use crate::worry::Worry;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operator {
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+impl Operator {
+    fn precedence(&self) -> u8 {
+        match self {
+            Operator::Add | Operator::Sub => 1,
+            Operator::Mul | Operator::Div => 2,
+        }
+    }
+
+    fn symbol(&self) -> char {
+        match self {
+            Operator::Add => '+',
+            Operator::Sub => '-',
+            Operator::Mul => '*',
+            Operator::Div => '/',
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expr {
+    Old,
+    Const(u64),
+    BinOp(Box<Expr>, Operator, Box<Expr>),
+}
+
+impl Expr {
+    /// Evaluates the expression with `u64` arithmetic, failing on overflow, negative results and
+    /// divisions by zero
+    pub fn eval(&self, old: u64) -> Result<u64, String> {
+        // Failures are rare, so the failing operation is only looked for once one happens
+        self.eval_checked(old)
+            .ok_or_else(|| self.eval_exact(&old).unwrap_err())
+    }
+
+    fn eval_checked(&self, old: u64) -> Option<u64> {
+        match self {
+            Expr::Old => Some(old),
+            Expr::Const(n) => Some(*n),
+            Expr::BinOp(a, op, b) => {
+                let (a, b) = (a.eval_operand(old)?, b.eval_operand(old)?);
+                match op {
+                    Operator::Add => a.checked_add(b),
+                    Operator::Sub => a.checked_sub(b),
+                    Operator::Mul => a.checked_mul(b),
+                    Operator::Div => a.checked_div(b),
+                }
+            }
+        }
+    }
+
+    /// Same as `eval_checked`, without recursing for leaves, which make up most puzzle operations
+    #[inline]
+    fn eval_operand(&self, old: u64) -> Option<u64> {
+        match self {
+            Expr::Old => Some(old),
+            Expr::Const(n) => Some(*n),
+            _ => self.eval_checked(old),
+        }
+    }
+
+    pub fn eval_exact<W: Worry>(&self, old: &W) -> Result<W, String> {
+        match self {
+            Expr::Old => Ok(old.clone()),
+            Expr::Const(n) => Ok(W::from_u64(*n)),
+            Expr::BinOp(a, op, b) => a.eval_exact(old)?.apply(*op, &b.eval_exact(old)?),
+        }
+    }
+
+    /// Describes the operation the way the puzzle narrates it, e.g. "is multiplied by 19"
+    pub fn describe(&self) -> String {
+        match self {
+            Expr::BinOp(a, op, b) if **a == Expr::Old => match (op, &**b) {
+                (Operator::Add, Expr::Old) => String::from("increases by itself"),
+                (Operator::Add, Expr::Const(n)) => format!("increases by {}", n),
+                (Operator::Sub, Expr::Const(n)) => format!("decreases by {}", n),
+                (Operator::Mul, Expr::Old) => String::from("is multiplied by itself"),
+                (Operator::Mul, Expr::Const(n)) => format!("is multiplied by {}", n),
+                (Operator::Div, Expr::Const(n)) => format!("is divided by {}", n),
+                _ => format!("becomes {}", self),
+            },
+            _ => format!("becomes {}", self),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub pos: usize,
+    pub msg: String,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.msg, self.pos)
+    }
+}
+
+impl FromStr for Expr {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser { input: s, pos: 0 };
+
+        let expr = parser.parse_expr(0)?;
+
+        parser.skip_whitespace();
+        if parser.pos < s.len() {
+            return Err(parser.error("Unexpected trailing input"));
+        }
+
+        Ok(expr)
+    }
+}
+
+/// Precedence-climbing parser for the grammar:
+///
+/// ```text
+/// expr    := operand (op operand)*
+/// operand := "old" | integer | "(" expr ")"
+/// op      := "+" | "-" | "*" | "/"
+/// ```
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, msg: &str) -> ParseError {
+        ParseError {
+            pos: self.pos,
+            msg: msg.to_string(),
+        }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    fn parse_expr(&mut self, min_precedence: u8) -> Result<Expr, ParseError> {
+        let mut lhs = self.parse_operand()?;
+
+        loop {
+            self.skip_whitespace();
+
+            let op = match self.rest().chars().next() {
+                Some('+') => Operator::Add,
+                Some('-') => Operator::Sub,
+                Some('*') => Operator::Mul,
+                Some('/') => Operator::Div,
+                _ => break,
+            };
+
+            if op.precedence() < min_precedence {
+                break;
+            }
+
+            self.pos += 1;
+            let rhs = self.parse_expr(op.precedence() + 1)?;
+            lhs = Expr::BinOp(Box::new(lhs), op, Box::new(rhs));
+        }
+
+        Ok(lhs)
+    }
+
+    fn parse_operand(&mut self) -> Result<Expr, ParseError> {
+        self.skip_whitespace();
+
+        let rest = self.rest();
+        match rest.chars().next() {
+            None => Err(self.error("Expected operand, found end of input")),
+            Some('(') => {
+                self.pos += 1;
+                let expr = self.parse_expr(0)?;
+                self.skip_whitespace();
+                if !self.rest().starts_with(')') {
+                    return Err(self.error("Expected ')'"));
+                }
+                self.pos += 1;
+                Ok(expr)
+            }
+            Some(c) if c.is_ascii_digit() => {
+                let len = rest
+                    .find(|c: char| !c.is_ascii_digit())
+                    .unwrap_or(rest.len());
+                let n = rest[..len]
+                    .parse()
+                    .map_err(|_| self.error("Integer too large"))?;
+                self.pos += len;
+                Ok(Expr::Const(n))
+            }
+            Some(_) => {
+                let len = rest
+                    .find(|c: char| !c.is_ascii_alphanumeric())
+                    .unwrap_or(rest.len());
+                if &rest[..len] == "old" {
+                    self.pos += len;
+                    Ok(Expr::Old)
+                } else {
+                    Err(self.error("Expected 'old', an integer or '('"))
+                }
+            }
+        }
+    }
+}
+
+impl Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Expr::Old => write!(f, "old"),
+            Expr::Const(n) => write!(f, "{}", n),
+            Expr::BinOp(a, op, b) => {
+                // Parenthesize operands binding looser than `op`, and right operands of the same
+                // precedence since the operators are left-associative
+                let needs_parens = |e: &Expr, right: bool| match e {
+                    Expr::BinOp(_, o, _) => {
+                        o.precedence() < op.precedence()
+                            || (right && o.precedence() == op.precedence())
+                    }
+                    _ => false,
+                };
+
+                if needs_parens(a, false) {
+                    write!(f, "({})", a)?;
+                } else {
+                    write!(f, "{}", a)?;
+                }
+
+                write!(f, " {} ", op.symbol())?;
+
+                if needs_parens(b, true) {
+                    write!(f, "({})", b)
+                } else {
+                    write!(f, "{}", b)
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_puzzle_operations() {
+        let sq: Expr = "old * old".parse().unwrap();
+        let add: Expr = "old + 6".parse().unwrap();
+
+        assert_eq!(sq.eval(7), Ok(49));
+        assert_eq!(add.eval(7), Ok(13));
+    }
+
+    #[test]
+    fn test_parse_variants() {
+        assert_eq!("3 * old".parse::<Expr>().unwrap().eval(5), Ok(15));
+        assert_eq!("old + old".parse::<Expr>().unwrap().eval(5), Ok(10));
+        assert_eq!("old - 2 - 1".parse::<Expr>().unwrap().eval(5), Ok(2));
+        assert_eq!("old + 2 * 3".parse::<Expr>().unwrap().eval(5), Ok(11));
+        assert_eq!("(old + 2) * 3".parse::<Expr>().unwrap().eval(5), Ok(21));
+        assert_eq!("old / (1 + 1)".parse::<Expr>().unwrap().eval(5), Ok(2));
+    }
+
+    #[test]
+    fn test_eval_errors() {
+        let eval = |s: &str, old| s.parse::<Expr>().unwrap().eval(old);
+
+        assert_eq!(
+            eval("old / 0", 5),
+            Err(String::from("5 / 0 divides by zero"))
+        );
+        assert_eq!(
+            eval("old - 100", 79),
+            Err(String::from("79 - 100 is negative"))
+        );
+        assert_eq!(
+            eval("old * old", u64::MAX),
+            Err(format!("{} * {} overflows", u64::MAX, u64::MAX))
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            "old ^ 2".parse::<Expr>(),
+            Err(ParseError {
+                pos: 4,
+                msg: String::from("Unexpected trailing input")
+            })
+        );
+        assert_eq!("old *".parse::<Expr>().unwrap_err().pos, 5);
+        assert_eq!("(old + 1".parse::<Expr>().unwrap_err().pos, 8);
+        assert_eq!("new + 1".parse::<Expr>().unwrap_err().pos, 0);
+        assert_eq!("".parse::<Expr>().unwrap_err().pos, 0);
+    }
+
+    #[test]
+    fn test_display_round_trip() {
+        for s in [
+            "old * old",
+            "3 * old",
+            "(old + 2) * 3",
+            "old - (2 - 1)",
+            "old / 2 / 3",
+            "old * (old / 2) + 1",
+        ] {
+            let expr: Expr = s.parse().unwrap();
+            assert_eq!(expr.to_string(), s);
+            assert_eq!(expr.to_string().parse::<Expr>().unwrap(), expr);
+        }
+    }
+}
@@ -1,9 +1,20 @@
+use std::env;
 use std::fmt::Display;
+use std::io::{self, Write};
 use std::time::Instant;
 
 use aoc_common::get_input;
 use itertools::Itertools;
 
+use crate::monkey::{parse_monkeys, Monkey};
+use crate::sim::{extrapolate_inspections, RoundReport, Simulation};
+use crate::worry::{count_inspections, Arithmetic};
+
+mod expr;
+mod monkey;
+mod sim;
+mod worry;
+
 fn main() {
     let input = get_input("day11.txt");
 
@@ -16,14 +27,41 @@ fn main() {
     println!("Part 1: {}", r1);
     println!("Part 2: {}", r2);
     println!("Duration: {:.3}ms", t);
+
+    let args = env::args().skip(1).collect_vec();
+    match args.iter().map(String::as_str).collect_vec()[..] {
+        [] => {}
+        ["--verbose", n] => {
+            let n = n.parse().expect("Invalid number of rounds");
+            print_rounds(&parse_input(&input), n, true).unwrap_or_else(|e| panic!("{}", e));
+        }
+        ["--rounds", n] => {
+            let n = n.parse().expect("Invalid number of rounds");
+            let counts = extrapolate_inspections(&parse_input(&input), n)
+                .unwrap_or_else(|e| panic!("{}", e));
+            let business: u128 = counts
+                .iter()
+                .sorted()
+                .rev()
+                .take(2)
+                .map(|&c| c as u128)
+                .product();
+            println!("Monkey business after {} rounds: {}", n, business);
+        }
+        ["--exact", n] => {
+            let n = n.parse().expect("Invalid number of rounds");
+            for has_relief in [true, false] {
+                compare_arithmetic(&parse_input(&input), n, has_relief);
+            }
+        }
+        _ => panic!("Usage: day11 [--verbose ROUNDS | --rounds ROUNDS | --exact ROUNDS]"),
+    }
 }
 
 fn solve(input: &[String]) -> (impl Display, impl Display) {
-    let mut monkeys = parse_input(input);
-    let p1 = get_level(&mut monkeys, 20, true);
-
-    monkeys = parse_input(input);
-    let p2 = get_level(&mut monkeys, 10000, false);
+    let monkeys = parse_input(input);
+    let p1 = get_level(&monkeys, 20, true).unwrap_or_else(|e| panic!("{}", e));
+    let p2 = get_level(&monkeys, 10000, false).unwrap_or_else(|e| panic!("{}", e));
 
     assert_eq!(p1, 58056);
     assert_eq!(p2, 15048718170);
@@ -31,127 +69,70 @@ fn solve(input: &[String]) -> (impl Display, impl Display) {
     (p1, p2)
 }
 
-struct Monkey {
-    items: Vec<u64>,
-    op: Box<dyn Fn(u64) -> u64>,
-    get_target: Box<dyn Fn(u64) -> usize>,
-    modulo: u64,
-}
-
 fn parse_input(input: &[String]) -> Vec<Monkey> {
-    let mut monkeys = Vec::new();
-    let mut it = input.iter().filter(|s| !s.is_empty());
-
-    // Skip Monkey id
-    it.next();
-
-    loop {
-        let items = it
-            .next()
-            .unwrap()
-            .strip_prefix("  Starting items: ")
-            .unwrap()
-            .split(", ")
-            .map(|i| i.parse().unwrap())
-            .collect::<Vec<u64>>();
-
-        let op: Box<dyn Fn(u64) -> u64> = match it
-            .next()
-            .unwrap()
-            .strip_prefix("  Operation: new = ")
-            .unwrap()
-        {
-            "old * old" => Box::new(|v: u64| v * v),
-            op_str => {
-                if let Some(val) = op_str.strip_prefix("old + ") {
-                    let val = val.parse::<u64>().unwrap();
-                    Box::new(move |v: u64| v + val)
-                } else if let Some(val) = op_str.strip_prefix("old - ") {
-                    let val = val.parse::<u64>().unwrap();
-                    Box::new(move |v: u64| v - val)
-                } else if let Some(val) = op_str.strip_prefix("old * ") {
-                    let val = val.parse::<u64>().unwrap();
-                    Box::new(move |v: u64| v * val)
-                } else if let Some(val) = op_str.strip_prefix("old / ") {
-                    let val = val.parse::<u64>().unwrap();
-                    Box::new(move |v: u64| v / val)
-                } else {
-                    panic!("Unable to parse operation: {}", op_str);
-                }
-            }
-        };
-
-        let modulo = it
-            .next()
-            .unwrap()
-            .strip_prefix("  Test: divisible by ")
-            .unwrap()
-            .parse::<u64>()
-            .expect("Invalid modulo");
-
-        let tgt_true = it
-            .next()
-            .unwrap()
-            .strip_prefix("    If true: throw to monkey ")
-            .unwrap()
-            .parse::<usize>()
-            .expect("Invalid 'if true'");
-
-        let tgt_false = it
-            .next()
-            .unwrap()
-            .strip_prefix("    If false: throw to monkey ")
-            .unwrap()
-            .parse::<usize>()
-            .expect("Invalid 'if false'");
-
-        monkeys.push(Monkey {
-            items,
-            op,
-            get_target: Box::new(move |v| if v == 0 { tgt_true } else { tgt_false }),
-            modulo,
-        });
-
-        if it.next().is_none() {
-            break;
-        }
-    }
-
-    monkeys
+    parse_monkeys(input).unwrap_or_else(|e| panic!("Invalid input: {}", e))
 }
 
-fn get_level(monkeys: &mut [Monkey], nb_rounds: usize, has_relief: bool) -> u64 {
-    let mut inspections = vec![0; monkeys.len()];
-
-    let master_modulo: u64 = monkeys.iter().map(|m| m.modulo).product();
+fn get_level(monkeys: &[Monkey], nb_rounds: usize, has_relief: bool) -> Result<u64, String> {
+    let mut sim = Simulation::new(monkeys, has_relief)?;
 
     for _ in 0..nb_rounds {
-        for id in 0..monkeys.len() {
-            let items = monkeys[id].items.to_vec();
-            monkeys[id].items.clear();
+        sim.step()?;
+    }
 
-            for item in items {
-                inspections[id] += 1;
+    Ok(sim.monkey_business())
+}
 
-                let (v, tgt) = {
-                    let m = &monkeys[id];
+/// Prints the puzzle-style narrative of the first `nb_rounds` rounds, followed by the items held
+/// by each monkey and the inspection counts at the end of each round
+fn print_rounds(monkeys: &[Monkey], nb_rounds: usize, has_relief: bool) -> Result<(), String> {
+    let mut sim = Simulation::new(monkeys, has_relief)?;
+    let mut out = io::stdout().lock();
 
-                    let mut v = (m.op)(item) % master_modulo;
+    for _ in 0..nb_rounds {
+        let report = sim.step_with_log(&mut out)?;
+        print_report(&mut out, &report).map_err(|e| e.to_string())?;
+    }
 
-                    if has_relief {
-                        v /= 3;
-                    }
+    writeln!(
+        out,
+        "Total inspections: {}",
+        sim.inspections().iter().join(", ")
+    )
+    .map_err(|e| e.to_string())
+}
 
-                    let tgt = (m.get_target)(v % m.modulo);
-                    (v, tgt)
-                };
+/// Prints the items held by each monkey and the inspection counts at the end of a round
+fn print_report<W: Write>(out: &mut W, report: &RoundReport) -> io::Result<()> {
+    writeln!(out)?;
+    writeln!(out, "== After round {} ==", report.round)?;
+    for (id, items) in report.items.iter().enumerate() {
+        writeln!(out, "Monkey {}: {}", id, items.iter().join(", "))?;
+    }
+    for (id, n) in report.inspections.iter().enumerate() {
+        writeln!(out, "Monkey {} inspected items {} times.", id, n)?;
+    }
+    writeln!(out)
+}
 
-                monkeys[tgt].items.push(v);
-            }
+/// Checks that the modulo optimization gives the same inspection counts as exact arithmetic
+fn compare_arithmetic(monkeys: &[Monkey], nb_rounds: usize, has_relief: bool) {
+    let modular = count_inspections(monkeys, nb_rounds, has_relief, Arithmetic::Modular).unwrap();
+
+    println!(
+        "{} rounds {} relief, modular: {}",
+        nb_rounds,
+        if has_relief { "with" } else { "without" },
+        modular.iter().join(", ")
+    );
+
+    for arithmetic in [Arithmetic::Checked, Arithmetic::BigInt] {
+        match count_inspections(monkeys, nb_rounds, has_relief, arithmetic) {
+            Ok(counts) if counts == modular => println!("  {:?}: identical", arithmetic),
+            Ok(counts) => println!("  {:?}: differs: {}", arithmetic, counts.iter().join(", ")),
+            Err(e) => println!("  {:?}: {}", arithmetic, e),
         }
     }
-
-    inspections.iter().sorted().rev().take(2).product()
 }
 
 #[cfg(test)]
@@ -197,9 +178,10 @@ Monkey 3:
             .map(String::from)
             .collect_vec();
 
-        let mut monkeys = parse_input(&input);
+        let monkeys = parse_input(&input);
 
-        assert_eq!(get_level(&mut monkeys, 20, true), 10605);
+        assert_eq!(get_level(&monkeys, 20, true), Ok(10605));
+        assert_eq!(get_level(&monkeys, 20, true), Ok(10605));
     }
 
     #[test]
@@ -210,8 +192,207 @@ Monkey 3:
             .map(String::from)
             .collect_vec();
 
-        let mut monkeys = parse_input(&input);
+        let monkeys = parse_input(&input);
+
+        assert_eq!(get_level(&monkeys, 10000, false), Ok(2713310158));
+    }
+
+    #[test]
+    fn test_format() {
+        let input = TEST_INPUT
+            .split('\n')
+            .filter(|s| !s.is_empty())
+            .map(String::from)
+            .collect_vec();
+
+        let monkeys = parse_input(&input);
+
+        assert_eq!(monkeys.iter().join("\n"), TEST_INPUT);
+    }
+
+    #[test]
+    fn test_simulation_log() {
+        let input = TEST_INPUT
+            .split('\n')
+            .filter(|s| !s.is_empty())
+            .map(String::from)
+            .collect_vec();
+
+        let monkeys = parse_input(&input);
+        let mut sim = Simulation::new(&monkeys, true).unwrap();
+
+        let mut log = Vec::new();
+        let report = sim.step_with_log(&mut log).unwrap();
+        let log = String::from_utf8(log).unwrap();
+
+        assert!(log.starts_with(
+            "Monkey 0:
+  Monkey inspects an item with a worry level of 79.
+    Worry level is multiplied by 19 to 1501.
+    Monkey gets bored with item. Worry level is divided by 3 to 500.
+    Current worry level is not divisible by 23.
+    Item with worry level 500 is thrown to monkey 3.
+"
+        ));
+        assert!(log.contains("    Worry level is multiplied by itself to 6241.\n"));
+        assert!(log.contains("    Worry level increases by 6 to 60.\n"));
+        assert!(!log.contains("reduced modulo"));
+
+        assert_eq!(report.round, 1);
+        assert_eq!(report.inspections, vec![2, 4, 3, 5]);
+        assert_eq!(
+            report.items,
+            vec![
+                vec![20, 23, 27, 26],
+                vec![2080, 25, 167, 207, 401, 1046],
+                vec![],
+                vec![]
+            ]
+        );
+
+        // Without relief, levels grow until they have to be reduced
+        let mut sim = Simulation::new(&monkeys, false).unwrap();
+        let mut log = Vec::new();
+        for _ in 0..3 {
+            sim.step_with_log(&mut log).unwrap();
+        }
+        let log = String::from_utf8(log).unwrap();
+
+        assert!(log.contains(
+            "    Worry level is multiplied by 19 to 118750.
+    Worry level is reduced modulo 96577 to 22173.
+"
+        ));
+    }
+
+    #[test]
+    fn test_extrapolate_inspections() {
+        let input = TEST_INPUT
+            .split('\n')
+            .filter(|s| !s.is_empty())
+            .map(String::from)
+            .collect_vec();
+
+        let monkeys = parse_input(&input);
+
+        let mut sim = Simulation::new(&monkeys, false).unwrap();
+        for _ in 0..10000 {
+            sim.step().unwrap();
+        }
+
+        assert_eq!(sim.inspections(), &[52166, 47830, 1938, 52013]);
+        assert_eq!(
+            extrapolate_inspections(&monkeys, 10000).unwrap(),
+            sim.inspections()
+        );
+        assert_eq!(
+            extrapolate_inspections(&monkeys, 20),
+            Ok(vec![99, 97, 8, 103])
+        );
+    }
+
+    #[test]
+    fn test_exact_arithmetic() {
+        let input = TEST_INPUT
+            .split('\n')
+            .filter(|s| !s.is_empty())
+            .map(String::from)
+            .collect_vec();
+
+        let monkeys = parse_input(&input);
+
+        for arithmetic in [Arithmetic::Modular, Arithmetic::Checked, Arithmetic::BigInt] {
+            assert_eq!(
+                count_inspections(&monkeys, 20, true, arithmetic),
+                Ok(vec![101, 95, 7, 105])
+            );
+        }
+
+        assert_eq!(
+            count_inspections(&monkeys, 20, false, Arithmetic::BigInt),
+            Ok(vec![99, 97, 8, 103])
+        );
+
+        let err = count_inspections(&monkeys, 20, false, Arithmetic::Checked).unwrap_err();
+        assert!(err.contains("overflows"), "{}", err);
+    }
 
-        assert_eq!(get_level(&mut monkeys, 10000, false), 2713310158);
+    #[test]
+    fn test_exact_arithmetic_errors() {
+        let mut monkeys = parse_input(
+            &TEST_INPUT
+                .split('\n')
+                .filter(|s| !s.is_empty())
+                .map(String::from)
+                .collect_vec(),
+        );
+        monkeys[0].op = "old - 100".parse().unwrap();
+
+        let negative = Err(String::from(
+            "Round 1, monkey 0: unable to compute old - 100 with old = 79: 79 - 100 is negative",
+        ));
+        for arithmetic in [Arithmetic::Modular, Arithmetic::Checked, Arithmetic::BigInt] {
+            assert_eq!(count_inspections(&monkeys, 1, true, arithmetic), negative);
+        }
+        assert_eq!(extrapolate_inspections(&monkeys, 1), negative);
+
+        monkeys[0].op = "old / 0".parse().unwrap();
+        assert_eq!(
+            get_level(&monkeys, 1, true),
+            Err(String::from(
+                "Round 1, monkey 0: unable to compute old / 0 with old = 79: 79 / 0 divides by zero"
+            ))
+        );
+    }
+
+    #[test]
+    fn test_divisor_product_overflow() {
+        let mut monkeys = parse_input(
+            &TEST_INPUT
+                .split('\n')
+                .filter(|s| !s.is_empty())
+                .map(String::from)
+                .collect_vec(),
+        );
+        monkeys[0].divisor = u64::MAX / 2;
+
+        let overflow = Err(String::from("The product of the divisors overflows"));
+        assert_eq!(
+            count_inspections(&monkeys, 1, true, Arithmetic::Modular),
+            overflow
+        );
+        assert_eq!(extrapolate_inspections(&monkeys, 1), overflow);
+        assert!(count_inspections(&monkeys, 1, true, Arithmetic::BigInt).is_ok());
+    }
+
+    #[test]
+    fn test_invalid_monkeys() {
+        let monkeys = parse_input(
+            &TEST_INPUT
+                .split('\n')
+                .filter(|s| !s.is_empty())
+                .map(String::from)
+                .collect_vec(),
+        );
+
+        let mut zero = monkeys.clone();
+        zero[1].divisor = 0;
+        let mut target = monkeys.clone();
+        target[2].if_false = 4;
+
+        for (monkeys, error) in [
+            (zero, "Monkey 1 has a divisor of 0"),
+            (
+                target,
+                "Monkey 2 throws to monkey 4, but there are only 4 monkeys",
+            ),
+        ] {
+            let error = Err(String::from(error));
+            for arithmetic in [Arithmetic::Modular, Arithmetic::Checked, Arithmetic::BigInt] {
+                assert_eq!(count_inspections(&monkeys, 1, true, arithmetic), error);
+            }
+            assert_eq!(extrapolate_inspections(&monkeys, 1), error);
+            assert!(Simulation::new(&monkeys, false).is_err());
+        }
     }
 }
@@ -0,0 +1,221 @@
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use itertools::Itertools;
+
+use crate::expr::Expr;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Monkey {
+    pub id: usize,
+    pub items: Vec<u64>,
+    pub op: Expr,
+    pub divisor: u64,
+    pub if_true: usize,
+    pub if_false: usize,
+}
+
+impl Monkey {
+    /// Describes a failure of the operation on the item with worry level `old`
+    pub fn operation_error<T: Display>(&self, round: usize, old: &T, e: &str) -> String {
+        format!(
+            "Round {}, monkey {}: unable to compute {} with old = {}: {}",
+            round, self.id, self.op, old, e
+        )
+    }
+
+    pub fn get_target(&self, worry: u64) -> usize {
+        if worry.is_multiple_of(self.divisor) {
+            self.if_true
+        } else {
+            self.if_false
+        }
+    }
+}
+
+fn parse_line<'a>(line: Option<&'a str>, prefix: &str) -> Result<&'a str, String> {
+    let line = line.ok_or_else(|| format!("Missing line starting with '{}'", prefix.trim()))?;
+
+    line.strip_prefix(prefix)
+        .ok_or_else(|| format!("Expected '{}', got '{}'", prefix.trim(), line))
+}
+
+fn parse_number<T: FromStr>(s: &str, what: &str) -> Result<T, String> {
+    s.trim()
+        .parse()
+        .map_err(|_| format!("Invalid {}: '{}'", what, s))
+}
+
+impl FromStr for Monkey {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines().filter(|l| !l.trim().is_empty());
+
+        let id = parse_line(lines.next(), "Monkey ")?;
+        let id = parse_number(id.strip_suffix(':').unwrap_or(id), "monkey id")?;
+
+        let items = parse_line(lines.next(), "  Starting items:")?;
+        let items = items
+            .split(',')
+            .filter(|i| !i.trim().is_empty())
+            .map(|i| parse_number(i, "item"))
+            .collect::<Result<Vec<u64>, _>>()?;
+
+        let op = parse_line(lines.next(), "  Operation: new = ")?;
+        let op = op
+            .parse()
+            .map_err(|e| format!("Unable to parse operation '{}': {}", op, e))?;
+
+        let divisor = parse_line(lines.next(), "  Test: divisible by ")?;
+        let divisor = parse_number(divisor, "divisor")?;
+
+        let if_true = parse_line(lines.next(), "    If true: throw to monkey ")?;
+        let if_true = parse_number(if_true, "'if true' target")?;
+
+        let if_false = parse_line(lines.next(), "    If false: throw to monkey ")?;
+        let if_false = parse_number(if_false, "'if false' target")?;
+
+        if let Some(l) = lines.next() {
+            return Err(format!("Unexpected line: '{}'", l));
+        }
+
+        Ok(Monkey {
+            id,
+            items,
+            op,
+            divisor,
+            if_true,
+            if_false,
+        })
+    }
+}
+
+impl Display for Monkey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Monkey {}:", self.id)?;
+        if self.items.is_empty() {
+            writeln!(f, "  Starting items:")?;
+        } else {
+            writeln!(f, "  Starting items: {}", self.items.iter().join(", "))?;
+        }
+        writeln!(f, "  Operation: new = {}", self.op)?;
+        writeln!(f, "  Test: divisible by {}", self.divisor)?;
+        writeln!(f, "    If true: throw to monkey {}", self.if_true)?;
+        writeln!(f, "    If false: throw to monkey {}", self.if_false)
+    }
+}
+
+pub fn parse_monkeys(input: &[String]) -> Result<Vec<Monkey>, String> {
+    let lines = input.iter().filter(|s| !s.is_empty()).collect_vec();
+
+    let monkeys = lines
+        .split_inclusive(|l| l.starts_with("    If false:"))
+        .map(|block| {
+            block
+                .iter()
+                .join("\n")
+                .parse::<Monkey>()
+                .map_err(|e| format!("{} in:\n{}", e, block.iter().join("\n")))
+        })
+        .collect::<Result<Vec<Monkey>, _>>()?;
+
+    validate(&monkeys)?;
+
+    Ok(monkeys)
+}
+
+pub fn validate(monkeys: &[Monkey]) -> Result<(), String> {
+    for (idx, m) in monkeys.iter().enumerate() {
+        if m.id != idx {
+            return Err(format!("Monkey {} is defined at position {}", m.id, idx));
+        }
+
+        if m.divisor == 0 {
+            return Err(format!("Monkey {} has a divisor of 0", m.id));
+        }
+
+        for tgt in [m.if_true, m.if_false] {
+            if tgt >= monkeys.len() {
+                return Err(format!(
+                    "Monkey {} throws to monkey {}, but there are only {} monkeys",
+                    m.id,
+                    tgt,
+                    monkeys.len()
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MONKEY: &str = "Monkey 0:
+  Starting items: 79, 98
+  Operation: new = old * 19
+  Test: divisible by 23
+    If true: throw to monkey 2
+    If false: throw to monkey 3
+";
+
+    fn lines(s: &str) -> Vec<String> {
+        s.lines().map(String::from).collect_vec()
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let monkey: Monkey = MONKEY.parse().unwrap();
+
+        assert_eq!(monkey.items, vec![79, 98]);
+        assert_eq!(monkey.divisor, 23);
+        assert_eq!(monkey.to_string(), MONKEY);
+        assert_eq!(monkey.to_string().parse::<Monkey>().unwrap(), monkey);
+    }
+
+    #[test]
+    fn test_empty_items() {
+        let mut monkey: Monkey = MONKEY.parse().unwrap();
+        monkey.items.clear();
+
+        assert_eq!(monkey.to_string().parse::<Monkey>().unwrap(), monkey);
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            MONKEY.replace("23", "x").parse::<Monkey>(),
+            Err(String::from("Invalid divisor: 'x'"))
+        );
+        assert!(MONKEY
+            .replace("old * 19", "old ^ 19")
+            .parse::<Monkey>()
+            .unwrap_err()
+            .starts_with("Unable to parse operation 'old ^ 19'"));
+    }
+
+    #[test]
+    fn test_validation() {
+        let monkey: Monkey = MONKEY.parse().unwrap();
+        assert_eq!(
+            parse_monkeys(&lines(MONKEY)),
+            Err(String::from(
+                "Monkey 0 throws to monkey 2, but there are only 1 monkeys"
+            ))
+        );
+
+        let zero = Monkey {
+            divisor: 0,
+            if_true: 0,
+            if_false: 0,
+            ..monkey
+        };
+        assert_eq!(
+            validate(&[zero]),
+            Err(String::from("Monkey 0 has a divisor of 0"))
+        );
+    }
+}
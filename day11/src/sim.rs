@@ -0,0 +1,275 @@
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+use crate::monkey::{self, Monkey};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RoundReport {
+    pub round: usize,
+    /// Number of items inspected by each monkey during this round
+    pub inspections: Vec<u64>,
+    /// Worry levels of the items held by each monkey at the end of this round
+    pub items: Vec<Vec<u64>>,
+}
+
+/// Product of all the divisors. Reducing worry levels modulo this keeps every divisibility test
+/// unchanged.
+pub fn master_modulo(monkeys: &[Monkey]) -> Result<u64, String> {
+    monkeys
+        .iter()
+        .try_fold(1u64, |acc, m| acc.checked_mul(m.divisor))
+        .ok_or_else(|| String::from("The product of the divisors overflows"))
+}
+
+pub struct Simulation {
+    monkeys: Vec<Monkey>,
+    inspections: Vec<u64>,
+    round: usize,
+    has_relief: bool,
+    master_modulo: u64,
+}
+
+impl Simulation {
+    pub fn new(monkeys: &[Monkey], has_relief: bool) -> Result<Self, String> {
+        monkey::validate(monkeys)?;
+
+        Ok(Self {
+            monkeys: monkeys.to_vec(),
+            inspections: vec![0; monkeys.len()],
+            round: 0,
+            has_relief,
+            master_modulo: master_modulo(monkeys)?,
+        })
+    }
+
+    pub fn inspections(&self) -> &[u64] {
+        &self.inspections
+    }
+
+    pub fn monkey_business(&self) -> u64 {
+        let mut inspections = self.inspections.clone();
+        inspections.sort_unstable_by(|a, b| b.cmp(a));
+
+        inspections.iter().take(2).product()
+    }
+
+    /// Runs one round. After an error, the simulation is left in the middle of the round.
+    pub fn step(&mut self) -> Result<(), String> {
+        self.run_round(None::<&mut io::Sink>).map(|_| ())
+    }
+
+    /// Runs one round, narrating every inspection to `log` the way the puzzle does
+    pub fn step_with_log<W: Write>(&mut self, log: &mut W) -> Result<RoundReport, String> {
+        let inspections = self.run_round(Some(log))?;
+
+        Ok(RoundReport {
+            round: self.round,
+            inspections,
+            items: self.monkeys.iter().map(|m| m.items.clone()).collect(),
+        })
+    }
+
+    /// Runs one round, returning the number of items inspected by each monkey. Nothing is
+    /// formatted unless there is a log.
+    fn run_round<W: Write>(&mut self, mut log: Option<&mut W>) -> Result<Vec<u64>, String> {
+        let mut inspections = vec![0; self.monkeys.len()];
+
+        for (id, count) in inspections.iter_mut().enumerate() {
+            if let Some(log) = log.as_deref_mut() {
+                writeln!(log, "Monkey {}:", id).map_err(log_error)?;
+            }
+
+            let mut items = std::mem::take(&mut self.monkeys[id].items);
+
+            for item in items.drain(..) {
+                *count += 1;
+
+                let m = &self.monkeys[id];
+                let raw =
+                    m.op.eval(item)
+                        .map_err(|e| m.operation_error(self.round + 1, &item, &e))?;
+                let worry = raw % self.master_modulo;
+                let v = if self.has_relief { worry / 3 } else { worry };
+                let tgt = m.get_target(v);
+
+                if let Some(log) = log.as_deref_mut() {
+                    let reduced = (worry != raw).then_some((self.master_modulo, worry));
+                    narrate(
+                        log,
+                        m,
+                        item,
+                        raw,
+                        reduced,
+                        self.has_relief.then_some(v),
+                        tgt,
+                    )
+                    .map_err(log_error)?;
+                }
+
+                self.monkeys[tgt].items.push(v);
+            }
+
+            // Hand the emptied buffer back to avoid reallocating it next round
+            if self.monkeys[id].items.is_empty() {
+                self.monkeys[id].items = items;
+            }
+        }
+
+        self.round += 1;
+        for (total, n) in self.inspections.iter_mut().zip(&inspections) {
+            *total += n;
+        }
+
+        Ok(inspections)
+    }
+}
+
+fn log_error(e: io::Error) -> String {
+    format!("Unable to write the log: {}", e)
+}
+
+/// Writes the puzzle's description of `m` inspecting `item`, where `worry` is the level after the
+/// operation, `reduced` the modulo and the level it was reduced to if that changed it, and
+/// `relieved` the level after relief, if any
+fn narrate<W: Write>(
+    log: &mut W,
+    m: &Monkey,
+    item: u64,
+    worry: u64,
+    reduced: Option<(u64, u64)>,
+    relieved: Option<u64>,
+    tgt: usize,
+) -> io::Result<()> {
+    writeln!(
+        log,
+        "  Monkey inspects an item with a worry level of {}.",
+        item
+    )?;
+    writeln!(log, "    Worry level {} to {}.", m.op.describe(), worry)?;
+
+    let worry = match reduced {
+        Some((modulo, v)) => {
+            writeln!(
+                log,
+                "    Worry level is reduced modulo {} to {}.",
+                modulo, v
+            )?;
+            v
+        }
+        None => worry,
+    };
+
+    let v = match relieved {
+        Some(v) => {
+            writeln!(
+                log,
+                "    Monkey gets bored with item. Worry level is divided by 3 to {}.",
+                v
+            )?;
+            v
+        }
+        None => worry,
+    };
+
+    if v.is_multiple_of(m.divisor) {
+        writeln!(
+            log,
+            "    Current worry level is divisible by {}.",
+            m.divisor
+        )?;
+    } else {
+        writeln!(
+            log,
+            "    Current worry level is not divisible by {}.",
+            m.divisor
+        )?;
+    }
+
+    writeln!(
+        log,
+        "    Item with worry level {} is thrown to monkey {}.",
+        v, tgt
+    )
+}
+
+/// Computes the number of inspections done by each monkey after `nb_rounds` rounds without
+/// relief, without simulating every round.
+///
+/// Items never interact with each other, so each one can be followed on its own. Under
+/// `master_modulo`, an item's state at the start of a round (its monkey and worry level) can only
+/// take a finite number of values, so its trajectory eventually becomes periodic. Once the cycle
+/// is found, the remaining rounds are extrapolated from the inspections done during one period.
+pub fn extrapolate_inspections(monkeys: &[Monkey], nb_rounds: u64) -> Result<Vec<u64>, String> {
+    monkey::validate(monkeys)?;
+    let master_modulo = master_modulo(monkeys)?;
+    let mut totals = vec![0; monkeys.len()];
+
+    for (start, &item) in monkeys
+        .iter()
+        .flat_map(|m| m.items.iter().map(move |i| (m.id, i)))
+    {
+        let mut state = (start, item % master_modulo);
+        let mut seen = HashMap::from([(state, 0)]);
+        // prefix[r][m] is the number of inspections done by monkey m during the first r rounds
+        let mut prefix = vec![vec![0u64; monkeys.len()]];
+
+        let counts = loop {
+            let round = prefix.len() - 1;
+            if round as u64 == nb_rounds {
+                break prefix[round].clone();
+            }
+
+            let mut counts = prefix[round].clone();
+            state = play_round(monkeys, round + 1, state, master_modulo, &mut counts)?;
+            prefix.push(counts);
+
+            if let Some(&cycle_start) = seen.get(&state) {
+                let cycle_len = (round + 1 - cycle_start) as u64;
+                let remaining = nb_rounds - cycle_start as u64;
+                let (nb_cycles, rem) = (remaining / cycle_len, (remaining % cycle_len) as usize);
+
+                break (0..monkeys.len())
+                    .map(|m| {
+                        let per_cycle = prefix[round + 1][m] - prefix[cycle_start][m];
+                        prefix[cycle_start + rem][m] + nb_cycles * per_cycle
+                    })
+                    .collect();
+            }
+
+            seen.insert(state, round + 1);
+        };
+
+        for (total, n) in totals.iter_mut().zip(counts) {
+            *total += n;
+        }
+    }
+
+    Ok(totals)
+}
+
+/// Follows a single item through one round, returning its state at the start of the next one
+fn play_round(
+    monkeys: &[Monkey],
+    round: usize,
+    (mut id, mut worry): (usize, u64),
+    master_modulo: u64,
+    counts: &mut [u64],
+) -> Result<(usize, u64), String> {
+    loop {
+        let m = &monkeys[id];
+        counts[id] += 1;
+
+        worry =
+            m.op.eval(worry)
+                .map_err(|e| m.operation_error(round, &worry, &e))?
+                % master_modulo;
+        let tgt = m.get_target(worry);
+
+        // Monkeys take their turn in order, so an item thrown to a later monkey is inspected
+        // again during the same round
+        if tgt <= id {
+            return Ok((tgt, worry));
+        }
+        id = tgt;
+    }
+}
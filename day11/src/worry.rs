@@ -0,0 +1,147 @@
+use std::fmt::Display;
+
+use num_bigint::BigUint;
+
+use crate::expr::Operator;
+use crate::monkey::{self, Monkey};
+use crate::sim::Simulation;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arithmetic {
+    /// `u64` worry levels reduced by the product of all divisors after every operation
+    Modular,
+    /// Exact `u64` worry levels, failing on overflow
+    Checked,
+    /// Exact, arbitrary-precision worry levels
+    BigInt,
+}
+
+/// Worry level representation for exact simulations
+pub trait Worry: Clone + Display + Sized {
+    fn from_u64(v: u64) -> Self;
+
+    fn apply(&self, op: Operator, rhs: &Self) -> Result<Self, String>;
+
+    fn div_u64(&self, d: u64) -> Self;
+
+    fn is_multiple_of(&self, d: u64) -> bool;
+}
+
+fn op_error<W: Display>(op: Operator, lhs: &W, rhs: &W) -> String {
+    match op {
+        Operator::Add => format!("{} + {} overflows", lhs, rhs),
+        Operator::Sub => format!("{} - {} is negative", lhs, rhs),
+        Operator::Mul => format!("{} * {} overflows", lhs, rhs),
+        Operator::Div => format!("{} / {} divides by zero", lhs, rhs),
+    }
+}
+
+impl Worry for u64 {
+    fn from_u64(v: u64) -> Self {
+        v
+    }
+
+    fn apply(&self, op: Operator, rhs: &Self) -> Result<Self, String> {
+        let res = match op {
+            Operator::Add => self.checked_add(*rhs),
+            Operator::Sub => self.checked_sub(*rhs),
+            Operator::Mul => self.checked_mul(*rhs),
+            Operator::Div => self.checked_div(*rhs),
+        };
+
+        res.ok_or_else(|| op_error(op, self, rhs))
+    }
+
+    fn div_u64(&self, d: u64) -> Self {
+        self / d
+    }
+
+    fn is_multiple_of(&self, d: u64) -> bool {
+        u64::is_multiple_of(*self, d)
+    }
+}
+
+impl Worry for BigUint {
+    fn from_u64(v: u64) -> Self {
+        BigUint::from(v)
+    }
+
+    fn apply(&self, op: Operator, rhs: &Self) -> Result<Self, String> {
+        let res = match op {
+            Operator::Add => Some(self + rhs),
+            Operator::Sub => (self >= rhs).then(|| self - rhs),
+            Operator::Mul => Some(self * rhs),
+            Operator::Div => (rhs.bits() > 0).then(|| self / rhs),
+        };
+
+        res.ok_or_else(|| op_error(op, self, rhs))
+    }
+
+    fn div_u64(&self, d: u64) -> Self {
+        self / d
+    }
+
+    fn is_multiple_of(&self, d: u64) -> bool {
+        (self % d).bits() == 0
+    }
+}
+
+/// Counts the number of items inspected by each monkey after `nb_rounds` rounds
+pub fn count_inspections(
+    monkeys: &[Monkey],
+    nb_rounds: usize,
+    has_relief: bool,
+    arithmetic: Arithmetic,
+) -> Result<Vec<u64>, String> {
+    monkey::validate(monkeys)?;
+
+    match arithmetic {
+        Arithmetic::Modular => {
+            let mut sim = Simulation::new(monkeys, has_relief)?;
+            for _ in 0..nb_rounds {
+                sim.step()?;
+            }
+
+            Ok(sim.inspections().to_vec())
+        }
+        Arithmetic::Checked => count_exact_inspections::<u64>(monkeys, nb_rounds, has_relief),
+        Arithmetic::BigInt => count_exact_inspections::<BigUint>(monkeys, nb_rounds, has_relief),
+    }
+}
+
+fn count_exact_inspections<W: Worry>(
+    monkeys: &[Monkey],
+    nb_rounds: usize,
+    has_relief: bool,
+) -> Result<Vec<u64>, String> {
+    let mut items = monkeys
+        .iter()
+        .map(|m| m.items.iter().map(|&i| W::from_u64(i)).collect::<Vec<W>>())
+        .collect::<Vec<_>>();
+    let mut inspections = vec![0; monkeys.len()];
+
+    for round in 1..=nb_rounds {
+        for (m, count) in monkeys.iter().zip(inspections.iter_mut()) {
+            for item in std::mem::take(&mut items[m.id]) {
+                *count += 1;
+
+                let mut v =
+                    m.op.eval_exact(&item)
+                        .map_err(|e| m.operation_error(round, &item, &e))?;
+
+                if has_relief {
+                    v = v.div_u64(3);
+                }
+
+                let tgt = if v.is_multiple_of(m.divisor) {
+                    m.if_true
+                } else {
+                    m.if_false
+                };
+                items[tgt].push(v);
+            }
+        }
+    }
+
+    Ok(inspections)
+}
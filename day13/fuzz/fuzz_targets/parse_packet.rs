@@ -0,0 +1,31 @@
+#![no_main]
+
+use std::cmp::Ordering;
+
+use libfuzzer_sys::fuzz_target;
+
+// day13 is a binary crate, so the packet module is included directly
+#[path = "../../src/packet.rs"]
+#[allow(dead_code)]
+mod packet;
+
+use packet::Packet;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(s) = std::str::from_utf8(data) else {
+        return;
+    };
+
+    match s.parse::<Packet>() {
+        Ok(p) => {
+            let printed = p.to_string();
+            let reparsed = printed
+                .parse::<Packet>()
+                .expect("Printed packet should parse");
+
+            assert_eq!(reparsed.to_string(), printed);
+            assert_eq!(p.cmp(&reparsed), Ordering::Equal);
+        }
+        Err(e) => assert!(e.pos <= s.len()),
+    }
+});
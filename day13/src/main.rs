@@ -1,31 +1,52 @@
-use std::cmp::Ordering;
+use std::env;
 use std::fmt::Display;
 use std::time::Instant;
 
 use itertools::Itertools;
-use serde_json::Value;
 
 use aoc_common::get_input;
 
+use crate::packet::Packet;
+use crate::trace::explain;
+
+mod packet;
+mod trace;
+
 fn main() {
     let input = get_input("day13.txt");
 
     let start = Instant::now();
 
-    let values = parse_values(&input);
+    let packets = parse_packets(&input);
 
-    let (r1, r2) = solve(&values);
+    let (r1, r2) = solve(&packets);
 
     let t = start.elapsed().as_nanos() as f64 / 1000.0;
 
     println!("Part 1: {}", r1);
     println!("Part 2: {}", r2);
     println!("Duration: {:.3}μs", t);
+
+    if env::args().nth(1).as_deref() == Some("--explain") {
+        explain_pairs(&packets);
+    }
 }
 
-fn solve(values: &[Value]) -> (impl Display, impl Display) {
-    let p1 = get_ordered_pair_index_sum(values);
-    let p2 = get_decoder_key(values);
+fn explain_pairs(packets: &[Packet]) {
+    for (idx, (p1, p2)) in packets.iter().tuples().enumerate() {
+        println!("== Pair {} ==", idx + 1);
+        let cmp = explain(p1, p2);
+        print!("{}", cmp);
+        if cmp.reason().is_none() {
+            println!("- Packets are equal, so inputs are in the right order");
+        }
+        println!();
+    }
+}
+
+fn solve(packets: &[Packet]) -> (impl Display, impl Display) {
+    let p1 = get_ordered_pair_index_sum(packets);
+    let p2 = get_decoder_key(packets);
 
     assert_eq!(p1, 6076);
     assert_eq!(p2, 24805);
@@ -33,39 +54,39 @@ fn solve(values: &[Value]) -> (impl Display, impl Display) {
     (p1, p2)
 }
 
-fn parse_values(input: &[String]) -> Vec<Value> {
+fn parse_packets(input: &[String]) -> Vec<Packet> {
     input
         .iter()
-        .filter(|s| !s.is_empty())
-        .map(|s| serde_json::from_str(s).unwrap())
+        .enumerate()
+        .filter(|(_, s)| !s.is_empty())
+        .map(|(idx, s)| {
+            s.parse()
+                .unwrap_or_else(|e| panic!("Invalid packet on line {}: {}", idx + 1, e))
+        })
         .collect_vec()
 }
 
-fn get_ordered_pair_index_sum(values: &[Value]) -> usize {
-    let mut sum = 0;
-
-    for (idx, mut chunk) in values.iter().chunks(2).into_iter().enumerate() {
-        let v1 = chunk.next().unwrap();
-        let v2 = chunk.next().unwrap();
-        if get_ordering(v1, v2) != Ordering::Greater {
-            sum += idx + 1
-        }
-    }
-
-    sum
+fn get_ordered_pair_index_sum(packets: &[Packet]) -> usize {
+    packets
+        .iter()
+        .tuples()
+        .enumerate()
+        .filter(|(_, (p1, p2))| p1 <= p2)
+        .map(|(idx, _)| idx + 1)
+        .sum()
 }
 
-fn get_decoder_key(values: &[Value]) -> usize {
-    let key2 = serde_json::from_str("[[2]]").unwrap();
-    let key6 = serde_json::from_str("[[6]]").unwrap();
+fn get_decoder_key(packets: &[Packet]) -> usize {
+    let key2 = Packet::List(vec![Packet::List(vec![Packet::Int(2)])]);
+    let key6 = Packet::List(vec![Packet::List(vec![Packet::Int(6)])]);
     let mut count2 = 0;
     let mut count6 = 0;
 
-    for v in values {
-        if get_ordering(v, &key2) == Ordering::Less {
+    for p in packets {
+        if p < &key2 {
             count2 += 1;
             count6 += 1;
-        } else if get_ordering(v, &key6) == Ordering::Less {
+        } else if p < &key6 {
             count6 += 1;
         }
     }
@@ -73,47 +94,12 @@ fn get_decoder_key(values: &[Value]) -> usize {
     (count2 + 1) * (count6 + 2)
 }
 
-fn get_ordering(v1: &Value, v2: &Value) -> Ordering {
-    if v1.is_number() && v2.is_number() {
-        let a = v1.as_u64().unwrap();
-        let b = v2.as_u64().unwrap();
-
-        return a.cmp(&b);
-    }
-
-    if v1.is_array() && v2.is_array() {
-        let arr1 = v1.as_array().unwrap();
-        let arr2 = v2.as_array().unwrap();
-
-        for (a, b) in arr1.iter().zip(arr2.iter()) {
-            let ord = get_ordering(a, b);
-
-            if ord != Ordering::Equal {
-                return ord;
-            }
-        }
-
-        return arr1.len().cmp(&arr2.len());
-    }
-
-    if v1.is_number() {
-        let v = vec![v1.clone()];
-
-        return get_ordering(&Value::Array(v), v2);
-    }
-
-    if v2.is_number() {
-        let v = vec![v2.clone()];
-
-        return get_ordering(v1, &Value::Array(v));
-    }
-
-    Ordering::Less
-}
-
 #[cfg(test)]
 mod tests {
+    use std::cmp::Ordering;
+
     use itertools::Itertools;
+    use proptest::prelude::*;
 
     use super::*;
 
@@ -149,9 +135,9 @@ mod tests {
             .map(String::from)
             .collect_vec();
 
-        let values = parse_values(&input);
+        let packets = parse_packets(&input);
 
-        assert_eq!(get_ordered_pair_index_sum(&values), 13);
+        assert_eq!(get_ordered_pair_index_sum(&packets), 13);
     }
 
     #[test]
@@ -162,8 +148,126 @@ mod tests {
             .map(String::from)
             .collect_vec();
 
-        let values = parse_values(&input);
+        let packets = parse_packets(&input);
+
+        assert_eq!(get_decoder_key(&packets), 140);
+    }
 
-        assert_eq!(get_decoder_key(&values), 140);
+    #[test]
+    fn test_explain_agrees_with_ordering() {
+        let input = TEST_INPUT
+            .split('\n')
+            .filter(|s| !s.is_empty())
+            .map(String::from)
+            .collect_vec();
+
+        let packets = parse_packets(&input);
+
+        let results = packets
+            .iter()
+            .tuples()
+            .map(|(p1, p2)| {
+                let cmp = explain(p1, p2);
+                assert_eq!(cmp.ordering, p1.cmp(p2));
+                cmp.ordering
+            })
+            .collect_vec();
+
+        assert_eq!(
+            results,
+            vec![
+                Ordering::Less,
+                Ordering::Less,
+                Ordering::Greater,
+                Ordering::Less,
+                Ordering::Greater,
+                Ordering::Less,
+                Ordering::Greater,
+                Ordering::Greater
+            ]
+        );
+    }
+
+    fn packet_strategy() -> impl Strategy<Value = Packet> {
+        // Small integers make equal and mixed-type comparisons likely
+        let leaf = (0u64..8).prop_map(Packet::Int);
+
+        leaf.prop_recursive(4, 32, 4, |inner| {
+            prop::collection::vec(inner, 0..4).prop_map(Packet::List)
+        })
+    }
+
+    fn list_strategy() -> impl Strategy<Value = Packet> {
+        prop::collection::vec(packet_strategy(), 0..4).prop_map(Packet::List)
+    }
+
+    proptest! {
+        #[test]
+        fn prop_display_round_trip(p in packet_strategy()) {
+            let s = p.to_string();
+            prop_assert_eq!(s.parse::<Packet>().unwrap().to_string(), s);
+        }
+
+        #[test]
+        fn prop_antisymmetry(a in packet_strategy(), b in packet_strategy()) {
+            prop_assert_eq!(a.cmp(&b), b.cmp(&a).reverse());
+            prop_assert_eq!(a.cmp(&a), Ordering::Equal);
+        }
+
+        #[test]
+        fn prop_transitivity(
+            a in packet_strategy(),
+            b in packet_strategy(),
+            c in packet_strategy(),
+        ) {
+            if a <= b && b <= c {
+                prop_assert!(a <= c);
+            }
+            if a == b && b == c {
+                prop_assert!(a == c);
+            }
+        }
+
+        #[test]
+        fn prop_explain_agrees_with_ordering(a in packet_strategy(), b in packet_strategy()) {
+            let cmp = explain(&a, &b);
+
+            prop_assert_eq!(cmp.ordering, a.cmp(&b));
+            prop_assert_eq!(cmp.reason().is_none(), a == b);
+        }
+
+        #[test]
+        fn prop_sorting_is_consistent(packets in prop::collection::vec(packet_strategy(), 0..16)) {
+            let sorted = packets.iter().sorted().collect_vec();
+
+            for (i, a) in sorted.iter().enumerate() {
+                for b in &sorted[i + 1..] {
+                    prop_assert!(a <= b);
+                }
+            }
+        }
+
+        #[test]
+        fn prop_decoder_key_matches_sorting(
+            pairs in prop::collection::vec((list_strategy(), list_strategy()), 0..8),
+        ) {
+            let packets = pairs.into_iter().flat_map(|(a, b)| [a, b]).collect_vec();
+
+            let key2 = "[[2]]".parse::<Packet>().unwrap();
+            let key6 = "[[6]]".parse::<Packet>().unwrap();
+
+            // The dividers come first so a stable sort keeps them before any packet equal to them,
+            // which is what counting strictly smaller packets does
+            let positions = [(1, &key2), (2, &key6)]
+                .into_iter()
+                .chain(packets.iter().map(|p| (0, p)))
+                .sorted_by(|a, b| a.1.cmp(b.1))
+                .enumerate()
+                .filter(|(_, (tag, _))| *tag != 0)
+                .map(|(idx, _)| idx + 1)
+                .collect_vec();
+
+            prop_assert_eq!(get_decoder_key(&packets), positions.iter().product::<usize>());
+        }
     }
 }
@@ -0,0 +1,243 @@
+use std::cmp::Ordering;
+use std::fmt::{self, Display};
+use std::slice;
+use std::str::FromStr;
+
+/// Maximum number of nested lists in a parsed packet. Comparing, printing and dropping packets
+/// recurse into nested lists, so deeper input is rejected rather than overflowing the stack.
+pub const MAX_DEPTH: usize = 256;
+
+#[derive(Debug, Clone)]
+pub enum Packet {
+    Int(u64),
+    List(Vec<Packet>),
+}
+
+impl Packet {
+    /// Views the packet as a list, promoting an integer to a single-element list without cloning
+    pub fn as_slice(&self) -> &[Packet] {
+        match self {
+            Packet::Int(_) => slice::from_ref(self),
+            Packet::List(l) => l,
+        }
+    }
+}
+
+impl Ord for Packet {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Packet::Int(a), Packet::Int(b)) => a.cmp(b),
+            _ => self.as_slice().cmp(other.as_slice()),
+        }
+    }
+}
+
+impl PartialOrd for Packet {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+// Equality has to follow the puzzle's ordering, where `[1]` and `1` are equal
+impl PartialEq for Packet {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Packet {}
+
+impl Display for Packet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Packet::Int(n) => write!(f, "{}", n),
+            Packet::List(l) => {
+                write!(f, "[")?;
+                for (idx, p) in l.iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", p)?;
+                }
+                write!(f, "]")
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub pos: usize,
+    pub msg: String,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.msg, self.pos)
+    }
+}
+
+impl FromStr for Packet {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            input: s.as_bytes(),
+            pos: 0,
+            depth: 0,
+        };
+
+        let packet = parser.parse_packet()?;
+
+        if parser.pos < s.len() {
+            return Err(parser.error("Unexpected trailing input"));
+        }
+
+        Ok(packet)
+    }
+}
+
+struct Parser<'a> {
+    input: &'a [u8],
+    pos: usize,
+    /// Number of lists currently open
+    depth: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn error(&self, msg: &str) -> ParseError {
+        ParseError {
+            pos: self.pos,
+            msg: msg.to_string(),
+        }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.pos).copied()
+    }
+
+    fn parse_packet(&mut self) -> Result<Packet, ParseError> {
+        match self.peek() {
+            Some(b'[') => self.parse_list(),
+            Some(c) if c.is_ascii_digit() => self.parse_int(),
+            Some(_) => Err(self.error("Expected '[' or a digit")),
+            None => Err(self.error("Unexpected end of input")),
+        }
+    }
+
+    fn parse_list(&mut self) -> Result<Packet, ParseError> {
+        if self.depth == MAX_DEPTH {
+            return Err(self.error(&format!("Lists nest more than {} deep", MAX_DEPTH)));
+        }
+
+        let mut items = Vec::new();
+
+        self.pos += 1;
+        if self.peek() == Some(b']') {
+            self.pos += 1;
+            return Ok(Packet::List(items));
+        }
+
+        self.depth += 1;
+        loop {
+            items.push(self.parse_packet()?);
+
+            match self.peek() {
+                Some(b',') => self.pos += 1,
+                Some(b']') => {
+                    self.pos += 1;
+                    self.depth -= 1;
+                    return Ok(Packet::List(items));
+                }
+                Some(_) => return Err(self.error("Expected ',' or ']'")),
+                None => return Err(self.error("Unexpected end of input")),
+            }
+        }
+    }
+
+    fn parse_int(&mut self) -> Result<Packet, ParseError> {
+        let start = self.pos;
+        let mut n = 0u64;
+
+        while let Some(c) = self.peek().filter(u8::is_ascii_digit) {
+            n = n
+                .checked_mul(10)
+                .and_then(|n| n.checked_add((c - b'0') as u64))
+                .ok_or_else(|| ParseError {
+                    pos: start,
+                    msg: String::from("Integer too large"),
+                })?;
+            self.pos += 1;
+        }
+
+        Ok(Packet::Int(n))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_round_trip() {
+        for s in ["[]", "[[[]]]", "[1,[2,[3,[4,[5,6,7]]]],8,9]", "42"] {
+            assert_eq!(s.parse::<Packet>().unwrap().to_string(), s);
+        }
+    }
+
+    #[test]
+    fn test_ordering() {
+        let p = |s: &str| s.parse::<Packet>().unwrap();
+
+        assert!(p("[1,1,3,1,1]") < p("[1,1,5,1,1]"));
+        assert!(p("[[1],[2,3,4]]") < p("[[1],4]"));
+        assert!(p("[9]") > p("[[8,7,6]]"));
+        assert!(p("[[4,4],4,4]") < p("[[4,4],4,4,4]"));
+        assert!(p("[7,7,7,7]") > p("[7,7,7]"));
+        assert!(p("[]") < p("[3]"));
+        assert!(p("[[[]]]") > p("[[]]"));
+        assert!(p("[1,[2,[3,[4,[5,6,7]]]],8,9]") > p("[1,[2,[3,[4,[5,6,0]]]],8,9]"));
+        assert_eq!(p("[[1]]"), p("1"));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let err = |s: &str| s.parse::<Packet>().unwrap_err();
+
+        assert_eq!(
+            err("[1,2"),
+            ParseError {
+                pos: 4,
+                msg: String::from("Unexpected end of input")
+            }
+        );
+        assert_eq!(err("[1;2]").pos, 2);
+        assert_eq!(err("[1,-2]").pos, 3);
+        assert_eq!(err("[1,\"a\"]").pos, 3);
+        assert_eq!(err("[1.5]").pos, 2);
+        assert_eq!(err("[1]]").pos, 3);
+        assert_eq!(err("[99999999999999999999]").pos, 1);
+        assert_eq!(err("").pos, 0);
+    }
+
+    #[test]
+    fn test_nesting_limit() {
+        let nested = |depth: usize| format!("{}{}", "[".repeat(depth), "]".repeat(depth));
+
+        let deepest: Packet = nested(MAX_DEPTH).parse().unwrap();
+        assert_eq!(deepest.to_string(), nested(MAX_DEPTH));
+        assert!(deepest > nested(MAX_DEPTH - 1).parse().unwrap());
+
+        assert_eq!(
+            nested(MAX_DEPTH + 1).parse::<Packet>(),
+            Err(ParseError {
+                pos: MAX_DEPTH,
+                msg: format!("Lists nest more than {} deep", MAX_DEPTH)
+            })
+        );
+        assert_eq!(
+            "[".repeat(1_000_000).parse::<Packet>().unwrap_err().pos,
+            MAX_DEPTH
+        );
+    }
+}
@@ -0,0 +1,254 @@
+use std::cmp::Ordering;
+use std::fmt::{self, Display};
+use std::slice;
+
+use crate::packet::Packet;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reason {
+    LeftSmaller,
+    RightSmaller,
+    LeftRanOut,
+    RightRanOut,
+}
+
+/// A side of a comparison, borrowed from the packets being compared
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operand<'a> {
+    Packet(&'a Packet),
+    /// An integer converted to a list containing only that integer
+    Promoted(&'a Packet),
+}
+
+impl<'a> Operand<'a> {
+    /// Items of the operand, an integer being treated as a list containing only itself
+    fn items(self) -> &'a [Packet] {
+        match self {
+            Operand::Packet(Packet::List(l)) => l,
+            Operand::Packet(p) | Operand::Promoted(p) => slice::from_ref(p),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step<'a> {
+    Compare(Operand<'a>, Operand<'a>),
+    /// An integer compared to a list is converted to a list containing only that integer
+    Promote(Side, &'a Packet),
+    Verdict(Reason),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceLine<'a> {
+    pub depth: usize,
+    pub step: Step<'a>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Comparison<'a> {
+    pub ordering: Ordering,
+    pub trace: Vec<TraceLine<'a>>,
+}
+
+impl Comparison<'_> {
+    /// Why the packets are or aren't in the right order, or `None` if they are equal
+    pub fn reason(&self) -> Option<Reason> {
+        self.trace.iter().rev().find_map(|l| match l.step {
+            Step::Verdict(r) => Some(r),
+            _ => None,
+        })
+    }
+}
+
+/// Compares two packets like `Ord` does, recording each step the way the puzzle explains it
+pub fn explain<'a>(left: &'a Packet, right: &'a Packet) -> Comparison<'a> {
+    let mut trace = Vec::new();
+    let ordering = explain_rec(Operand::Packet(left), Operand::Packet(right), 0, &mut trace);
+
+    Comparison { ordering, trace }
+}
+
+fn explain_rec<'a>(
+    left: Operand<'a>,
+    right: Operand<'a>,
+    depth: usize,
+    trace: &mut Vec<TraceLine<'a>>,
+) -> Ordering {
+    push(trace, depth, Step::Compare(left, right));
+
+    match (left, right) {
+        (Operand::Packet(Packet::Int(a)), Operand::Packet(Packet::Int(b))) => {
+            let ordering = a.cmp(b);
+            match ordering {
+                Ordering::Less => push(trace, depth + 1, Step::Verdict(Reason::LeftSmaller)),
+                Ordering::Greater => push(trace, depth + 1, Step::Verdict(Reason::RightSmaller)),
+                Ordering::Equal => {}
+            }
+
+            ordering
+        }
+        (Operand::Packet(p @ Packet::Int(_)), _) => {
+            push(trace, depth + 1, Step::Promote(Side::Left, p));
+
+            explain_rec(Operand::Promoted(p), right, depth + 1, trace)
+        }
+        (_, Operand::Packet(p @ Packet::Int(_))) => {
+            push(trace, depth + 1, Step::Promote(Side::Right, p));
+
+            explain_rec(left, Operand::Promoted(p), depth + 1, trace)
+        }
+        _ => {
+            let (l, r) = (left.items(), right.items());
+            for (a, b) in l.iter().zip(r) {
+                let ordering =
+                    explain_rec(Operand::Packet(a), Operand::Packet(b), depth + 1, trace);
+                if ordering != Ordering::Equal {
+                    return ordering;
+                }
+            }
+
+            let ordering = l.len().cmp(&r.len());
+            match ordering {
+                Ordering::Less => push(trace, depth + 1, Step::Verdict(Reason::LeftRanOut)),
+                Ordering::Greater => push(trace, depth + 1, Step::Verdict(Reason::RightRanOut)),
+                Ordering::Equal => {}
+            }
+
+            ordering
+        }
+    }
+}
+
+fn push<'a>(trace: &mut Vec<TraceLine<'a>>, depth: usize, step: Step<'a>) {
+    trace.push(TraceLine { depth, step });
+}
+
+impl Display for Operand<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Operand::Packet(p) => write!(f, "{}", p),
+            Operand::Promoted(p) => write!(f, "[{}]", p),
+        }
+    }
+}
+
+impl Display for Step<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Step::Compare(l, r) => write!(f, "Compare {} vs {}", l, r),
+            Step::Promote(side, p) => {
+                let side = match side {
+                    Side::Left => "left",
+                    Side::Right => "right",
+                };
+                write!(
+                    f,
+                    "Mixed types; convert {} to [{}] and retry comparison",
+                    side, p
+                )
+            }
+            Step::Verdict(Reason::LeftSmaller) => {
+                write!(f, "Left side is smaller, so inputs are in the right order")
+            }
+            Step::Verdict(Reason::RightSmaller) => {
+                write!(
+                    f,
+                    "Right side is smaller, so inputs are not in the right order"
+                )
+            }
+            Step::Verdict(Reason::LeftRanOut) => {
+                write!(
+                    f,
+                    "Left side ran out of items, so inputs are in the right order"
+                )
+            }
+            Step::Verdict(Reason::RightRanOut) => {
+                write!(
+                    f,
+                    "Right side ran out of items, so inputs are not in the right order"
+                )
+            }
+        }
+    }
+}
+
+impl Display for Comparison<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for line in &self.trace {
+            writeln!(f, "{:indent$}- {}", "", line.step, indent = line.depth * 2)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn p(s: &str) -> Packet {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_explain_mixed_types() {
+        let (a, b) = (p("[[1],[2,3,4]]"), p("[[1],4]"));
+        let cmp = explain(&a, &b);
+
+        assert_eq!(cmp.ordering, Ordering::Less);
+        assert_eq!(cmp.reason(), Some(Reason::LeftSmaller));
+        assert_eq!(
+            cmp.to_string(),
+            "- Compare [[1],[2,3,4]] vs [[1],4]
+  - Compare [1] vs [1]
+    - Compare 1 vs 1
+  - Compare [2,3,4] vs 4
+    - Mixed types; convert right to [4] and retry comparison
+    - Compare [2,3,4] vs [4]
+      - Compare 2 vs 4
+        - Left side is smaller, so inputs are in the right order
+"
+        );
+    }
+
+    #[test]
+    fn test_explain_ran_out() {
+        let (a, b) = (p("[7,7,7,7]"), p("[7,7,7]"));
+        let cmp = explain(&a, &b);
+
+        assert_eq!(cmp.ordering, Ordering::Greater);
+        assert_eq!(cmp.reason(), Some(Reason::RightRanOut));
+        assert_eq!(
+            cmp.to_string(),
+            "- Compare [7,7,7,7] vs [7,7,7]
+  - Compare 7 vs 7
+  - Compare 7 vs 7
+  - Compare 7 vs 7
+  - Right side ran out of items, so inputs are not in the right order
+"
+        );
+
+        let (a, b) = (p("[[[]]]"), p("[[]]"));
+        let cmp = explain(&a, &b);
+        assert_eq!(cmp.reason(), Some(Reason::RightRanOut));
+
+        let (a, b) = (p("[]"), p("[3]"));
+        let cmp = explain(&a, &b);
+        assert_eq!(cmp.reason(), Some(Reason::LeftRanOut));
+    }
+
+    #[test]
+    fn test_explain_equal() {
+        let (a, b) = (p("[1,[2]]"), p("[1,2]"));
+        let cmp = explain(&a, &b);
+
+        assert_eq!(cmp.ordering, Ordering::Equal);
+        assert_eq!(cmp.reason(), None);
+    }
+}
@@ -0,0 +1,314 @@
+use std::ops::Range;
+
+use itertools::Itertools;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Pos {
+    pub x: i64,
+    pub y: i64,
+}
+
+impl Pos {
+    pub fn new(x: i64, y: i64) -> Self {
+        Pos { x, y }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Element {
+    Air,
+    Sand,
+    Rock,
+}
+
+/// A cave map growing on demand to fit whatever is set in it. Cells never set are air.
+#[derive(Debug, Clone, Default)]
+pub struct Grid {
+    rows: Vec<Vec<Element>>,
+    min_x: i64,
+    width: usize,
+    used_x: Option<(i64, i64)>,
+    max_rock_y: Option<i64>,
+}
+
+impl Grid {
+    /// Draws each path as rock, whatever the slope of its segments. See `parse_paths` to
+    /// validate them.
+    pub fn from_paths(paths: &[Vec<Pos>]) -> Self {
+        let mut grid = Grid::default();
+
+        for path in paths {
+            if let [p] = path.as_slice() {
+                grid.set(p.x, p.y, Element::Rock);
+            }
+
+            for (a, b) in path.iter().tuple_windows() {
+                for p in rasterize(*a, *b) {
+                    grid.set(p.x, p.y, Element::Rock);
+                }
+            }
+        }
+
+        grid
+    }
+
+    /// Depth of the lowest rock, if there is any
+    pub fn max_rock_y(&self) -> Option<i64> {
+        self.max_rock_y
+    }
+
+    /// Horizontal range of the cells set so far, which can be narrower than what is allocated
+    pub fn x_range(&self) -> Range<i64> {
+        self.used_x.map_or(0..0, |(min, max)| min..max + 1)
+    }
+
+    pub fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn get(&self, x: i64, y: i64) -> Element {
+        if x < self.min_x || y < 0 {
+            return Element::Air;
+        }
+
+        self.rows
+            .get(y as usize)
+            .and_then(|r| r.get((x - self.min_x) as usize))
+            .copied()
+            .unwrap_or(Element::Air)
+    }
+
+    pub fn set(&mut self, x: i64, y: i64, element: Element) {
+        assert!(y >= 0, "Negative depth: {}", y);
+
+        self.grow_to(x, y);
+        self.rows[y as usize][(x - self.min_x) as usize] = element;
+        self.used_x = Some(
+            self.used_x
+                .map_or((x, x), |(min, max)| (min.min(x), max.max(x))),
+        );
+
+        if element == Element::Rock {
+            self.max_rock_y = self.max_rock_y.max(Some(y));
+        }
+    }
+
+    fn grow_to(&mut self, x: i64, y: i64) {
+        if self.width == 0 {
+            self.min_x = x;
+            self.width = 1;
+            for r in self.rows.iter_mut() {
+                r.push(Element::Air);
+            }
+        }
+
+        if x < self.min_x {
+            // Grow by at least half the current width to keep repeated growth amortized
+            let n = ((self.min_x - x) as usize).max(self.width / 2);
+            for r in self.rows.iter_mut() {
+                r.splice(0..0, std::iter::repeat_n(Element::Air, n));
+            }
+            self.min_x -= n as i64;
+            self.width += n;
+        } else if x >= self.min_x + self.width as i64 {
+            let n = ((x - self.min_x) as usize + 1 - self.width).max(self.width / 2);
+            self.width += n;
+            for r in self.rows.iter_mut() {
+                r.resize(self.width, Element::Air);
+            }
+        }
+
+        while self.rows.len() <= y as usize {
+            self.rows.push(vec![Element::Air; self.width]);
+        }
+    }
+}
+
+/// Which segments rock paths may contain
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Slopes {
+    /// Horizontal and vertical segments only, as in the puzzle
+    Straight,
+    /// Also 45° diagonals
+    Diagonal,
+    /// Any segment, drawn with Bresenham's algorithm
+    Any,
+}
+
+impl Slopes {
+    fn allows(self, a: Pos, b: Pos) -> bool {
+        let (dx, dy) = ((b.x - a.x).abs(), (b.y - a.y).abs());
+
+        match self {
+            Slopes::Straight => dx == 0 || dy == 0,
+            Slopes::Diagonal => dx == 0 || dy == 0 || dx == dy,
+            Slopes::Any => true,
+        }
+    }
+}
+
+/// Cells of the segment from `a` to `b`, both included, using Bresenham's algorithm. Horizontal,
+/// vertical and 45° segments get exactly the cells one would expect.
+pub fn rasterize(a: Pos, b: Pos) -> impl Iterator<Item = Pos> {
+    let (dx, dy) = ((b.x - a.x).abs(), -(b.y - a.y).abs());
+    let (sx, sy) = ((b.x - a.x).signum(), (b.y - a.y).signum());
+    let mut err = dx + dy;
+    let mut next = Some(a);
+
+    std::iter::from_fn(move || {
+        let p = next?;
+
+        next = (p != b).then(|| {
+            let (mut q, e2) = (p, 2 * err);
+            if e2 >= dy {
+                err += dy;
+                q.x += sx;
+            }
+            if e2 <= dx {
+                err += dx;
+                q.y += sy;
+            }
+            q
+        });
+
+        Some(p)
+    })
+}
+
+fn parse_pos(s: &str) -> Result<Pos, String> {
+    let (x, y) = s
+        .trim()
+        .split_once(',')
+        .ok_or_else(|| format!("Expected 'x,y', got '{}'", s.trim()))?;
+
+    let x = x
+        .parse()
+        .map_err(|_| format!("Invalid coordinate: '{}'", x))?;
+    let y = y
+        .parse()
+        .map_err(|_| format!("Invalid coordinate: '{}'", y))?;
+
+    if y < 0 {
+        return Err(format!("Negative depth in {},{}", x, y));
+    }
+
+    Ok(Pos::new(x, y))
+}
+
+fn parse_path(s: &str, slopes: Slopes) -> Result<Vec<Pos>, String> {
+    let path = s
+        .split("->")
+        .map(parse_pos)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if let Some((a, b)) = path
+        .iter()
+        .tuple_windows()
+        .find(|(a, b)| !slopes.allows(**a, **b))
+    {
+        return Err(format!(
+            "Unsupported segment {},{} -> {},{} with {:?} slopes",
+            a.x, a.y, b.x, b.y, slopes
+        ));
+    }
+
+    Ok(path)
+}
+
+/// Parses one rock path per non-empty line, rejecting segments not allowed by `slopes`
+pub fn parse_paths(input: &[String], slopes: Slopes) -> Result<Vec<Vec<Pos>>, String> {
+    input
+        .iter()
+        .enumerate()
+        .filter(|(_, s)| !s.is_empty())
+        .map(|(idx, s)| {
+            parse_path(s, slopes).map_err(|e| format!("Line {}: {}: '{}'", idx + 1, e, s))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lines(s: &str) -> Vec<String> {
+        s.lines().map(String::from).collect_vec()
+    }
+
+    #[test]
+    fn test_rasterize() {
+        let cells = |a, b| rasterize(a, b).collect_vec();
+
+        assert_eq!(
+            cells(Pos::new(498, 4), Pos::new(498, 6)),
+            vec![Pos::new(498, 4), Pos::new(498, 5), Pos::new(498, 6)]
+        );
+        assert_eq!(
+            cells(Pos::new(3, 3), Pos::new(0, 0)),
+            vec![
+                Pos::new(3, 3),
+                Pos::new(2, 2),
+                Pos::new(1, 1),
+                Pos::new(0, 0)
+            ]
+        );
+        assert_eq!(
+            cells(Pos::new(0, 0), Pos::new(4, 2)),
+            vec![
+                Pos::new(0, 0),
+                Pos::new(1, 1),
+                Pos::new(2, 1),
+                Pos::new(3, 2),
+                Pos::new(4, 2)
+            ]
+        );
+        assert_eq!(cells(Pos::new(7, 7), Pos::new(7, 7)), vec![Pos::new(7, 7)]);
+    }
+
+    #[test]
+    fn test_diagonal_walls() {
+        let paths = parse_paths(&lines("495,2 -> 498,5 -> 501,2"), Slopes::Diagonal).unwrap();
+        let grid = Grid::from_paths(&paths);
+
+        assert_eq!(grid.get(496, 3), Element::Rock);
+        assert_eq!(grid.get(498, 5), Element::Rock);
+        assert_eq!(grid.get(500, 3), Element::Rock);
+        assert_eq!(grid.get(497, 3), Element::Air);
+        assert_eq!(grid.max_rock_y(), Some(5));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let input = lines("498,4 -> 498,6\n\n498,4 -> 500,5");
+
+        assert_eq!(
+            parse_paths(&input, Slopes::Diagonal),
+            Err(String::from(
+                "Line 3: Unsupported segment 498,4 -> 500,5 with Diagonal slopes: '498,4 -> 500,5'"
+            ))
+        );
+        assert!(parse_paths(&input, Slopes::Any).is_ok());
+        assert_eq!(
+            parse_paths(&lines("0,0 -> 2,2"), Slopes::Straight),
+            Err(String::from(
+                "Line 1: Unsupported segment 0,0 -> 2,2 with Straight slopes: '0,0 -> 2,2'"
+            ))
+        );
+        assert_eq!(
+            parse_paths(&lines("498,4 -> 498,-6"), Slopes::Any),
+            Err(String::from(
+                "Line 1: Negative depth in 498,-6: '498,4 -> 498,-6'"
+            ))
+        );
+        assert_eq!(
+            parse_paths(&lines("498,4 -> 498"), Slopes::Any),
+            Err(String::from(
+                "Line 1: Expected 'x,y', got '498': '498,4 -> 498'"
+            ))
+        );
+        assert_eq!(
+            parse_paths(&lines("a,4"), Slopes::Any),
+            Err(String::from("Line 1: Invalid coordinate: 'a': 'a,4'"))
+        );
+    }
+}
@@ -0,0 +1,503 @@
+use std::fmt::{self, Display};
+use std::fs::{self, File};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::cave::{Element, Pos};
+
+/// Snapshot of the cave, covering every cell sand can come to rest in as well as the source and
+/// the floor
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    pub min_x: i64,
+    pub width: usize,
+    pub height: usize,
+    pub cells: Vec<Element>,
+    pub source: Pos,
+    pub grains: usize,
+}
+
+impl Frame {
+    pub fn get(&self, x: i64, y: i64) -> Element {
+        if x < self.min_x || x >= self.min_x + self.width as i64 || y < 0 {
+            return Element::Air;
+        }
+
+        self.cells
+            .get(y as usize * self.width + (x - self.min_x) as usize)
+            .copied()
+            .unwrap_or(Element::Air)
+    }
+
+    fn color(&self, x: i64, y: i64) -> u8 {
+        match self.get(x, y) {
+            Element::Air if (Pos { x, y }) == self.source => SOURCE,
+            Element::Air => AIR,
+            Element::Rock => ROCK,
+            Element::Sand => SAND,
+        }
+    }
+}
+
+impl Display for Frame {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for y in 0..self.height as i64 {
+            let row = (self.min_x..self.min_x + self.width as i64)
+                .map(|x| match self.color(x, y) {
+                    SOURCE => '+',
+                    ROCK => '#',
+                    SAND => 'o',
+                    _ => '.',
+                })
+                .collect::<String>();
+
+            writeln!(f, "{}", row)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Largest frame, in cells, that is worth animating or recording
+pub const MAX_FRAME_CELLS: u128 = 1 << 22;
+
+/// Receives frames while the simulation runs
+pub trait FrameSink {
+    /// Whether a frame should be captured now, given the number of grains at rest
+    fn wants_frame(&mut self, grains: usize) -> bool;
+
+    fn push(&mut self, frame: Frame);
+
+    /// Whether the final state should be captured once the simulation is over
+    fn wants_final_frame(&self) -> bool {
+        true
+    }
+}
+
+/// Discards every frame
+pub struct NoFrames;
+
+impl FrameSink for NoFrames {
+    fn wants_frame(&mut self, _grains: usize) -> bool {
+        false
+    }
+
+    fn push(&mut self, _frame: Frame) {}
+
+    fn wants_final_frame(&self) -> bool {
+        false
+    }
+}
+
+/// Destination of the frames kept by a `Recorder`
+pub trait FrameWriter {
+    fn write_frame(&mut self, frame: Frame) -> io::Result<()>;
+}
+
+impl FrameWriter for Vec<Frame> {
+    fn write_frame(&mut self, frame: Frame) -> io::Result<()> {
+        self.push(frame);
+        Ok(())
+    }
+}
+
+/// Passes one frame every `every` grains on to a writer as soon as it is captured. Recording stops
+/// at the first error, which `into_writer` returns.
+pub struct Recorder<F: FrameWriter> {
+    every: usize,
+    last: Option<usize>,
+    writer: F,
+    error: Option<io::Error>,
+}
+
+impl<F: FrameWriter> Recorder<F> {
+    pub fn new(every: usize, writer: F) -> Self {
+        Recorder {
+            every: every.max(1),
+            last: None,
+            writer,
+            error: None,
+        }
+    }
+
+    pub fn into_writer(self) -> io::Result<F> {
+        match self.error {
+            Some(e) => Err(e),
+            None => Ok(self.writer),
+        }
+    }
+}
+
+impl<F: FrameWriter> FrameSink for Recorder<F> {
+    fn wants_frame(&mut self, grains: usize) -> bool {
+        match self.last {
+            _ if self.error.is_some() => false,
+            Some(last) => grains >= last + self.every,
+            None => true,
+        }
+    }
+
+    fn push(&mut self, frame: Frame) {
+        if self.last == Some(frame.grains) {
+            return;
+        }
+
+        self.last = Some(frame.grains);
+        if let Err(e) = self.writer.write_frame(frame) {
+            self.error = Some(e);
+        }
+    }
+
+    fn wants_final_frame(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Draws frames in the terminal, skipping frames to show at most one every `interval`. Drawing
+/// stops at the first error, which `finish` returns.
+pub struct TerminalAnimation<W: Write> {
+    out: W,
+    interval: Duration,
+    last: Option<Instant>,
+    error: Option<io::Error>,
+}
+
+impl<W: Write> TerminalAnimation<W> {
+    pub fn new(out: W, interval: Duration) -> Self {
+        TerminalAnimation {
+            out,
+            interval,
+            last: None,
+            error: None,
+        }
+    }
+
+    pub fn finish(self) -> io::Result<()> {
+        match self.error {
+            Some(e) => Err(e),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<W: Write> FrameSink for TerminalAnimation<W> {
+    fn wants_frame(&mut self, _grains: usize) -> bool {
+        self.error.is_none() && self.last.is_none_or(|t| t.elapsed() >= self.interval)
+    }
+
+    fn push(&mut self, frame: Frame) {
+        if let Some(wait) = self
+            .last
+            .and_then(|t| self.interval.checked_sub(t.elapsed()))
+        {
+            thread::sleep(wait);
+        }
+        self.last = Some(Instant::now());
+
+        // Clear the screen and move the cursor home before drawing
+        if let Err(e) = writeln!(self.out, "\x1b[2J\x1b[H{}Grains: {}", frame, frame.grains)
+            .and_then(|_| self.out.flush())
+        {
+            self.error = Some(e);
+        }
+    }
+
+    fn wants_final_frame(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Ppm,
+    Png,
+}
+
+const AIR: u8 = 0;
+const ROCK: u8 = 1;
+const SAND: u8 = 2;
+const SOURCE: u8 = 3;
+
+const PALETTE: [[u8; 3]; 4] = [
+    [0x1e, 0x1e, 0x2e],
+    [0x7f, 0x84, 0x9c],
+    [0xf9, 0xe2, 0xaf],
+    [0xf3, 0x8b, 0xa8],
+];
+
+/// Palette indices of a frame, each cell scaled to a `scale` x `scale` square
+fn indexed_pixels(frame: &Frame, scale: usize) -> Vec<u8> {
+    (0..frame.height * scale)
+        .flat_map(|y| (0..frame.width * scale).map(move |x| (x, y)))
+        .map(|(x, y)| frame.color(frame.min_x + (x / scale) as i64, (y / scale) as i64))
+        .collect()
+}
+
+/// Encodes frames into an animated GIF the size of the first one
+pub struct GifWriter<W: Write> {
+    out: Option<W>,
+    encoder: Option<gif::Encoder<W>>,
+    scale: usize,
+    delay: Duration,
+    frames: usize,
+}
+
+impl<W: Write> GifWriter<W> {
+    pub fn new(out: W, scale: usize, delay: Duration) -> Self {
+        GifWriter {
+            out: Some(out),
+            encoder: None,
+            scale: scale.max(1),
+            delay,
+            frames: 0,
+        }
+    }
+
+    /// Ends the GIF, returning the number of frames written
+    pub fn finish(self) -> io::Result<usize> {
+        let encoder = self
+            .encoder
+            .ok_or_else(|| io::Error::other("No frames to write"))?;
+        encoder.into_inner()?.flush()?;
+
+        Ok(self.frames)
+    }
+}
+
+impl<W: Write> FrameWriter for GifWriter<W> {
+    fn write_frame(&mut self, frame: Frame) -> io::Result<()> {
+        let (w, h) = (
+            u16::try_from(frame.width * self.scale).map_err(io::Error::other)?,
+            u16::try_from(frame.height * self.scale).map_err(io::Error::other)?,
+        );
+
+        let encoder = match &mut self.encoder {
+            Some(encoder) => encoder,
+            None => {
+                // The output is only missing if creating the encoder failed before
+                let out = self
+                    .out
+                    .take()
+                    .ok_or_else(|| io::Error::other("The GIF encoder failed to start"))?;
+                let mut encoder = gif::Encoder::new(out, w, h, PALETTE.as_flattened())
+                    .map_err(io::Error::other)?;
+                encoder
+                    .set_repeat(gif::Repeat::Infinite)
+                    .map_err(io::Error::other)?;
+                self.encoder.insert(encoder)
+            }
+        };
+
+        let mut f = gif::Frame::from_indexed_pixels(w, h, indexed_pixels(&frame, self.scale), None);
+        f.delay = (self.delay.as_millis() / 10) as u16;
+        encoder.write_frame(&f).map_err(io::Error::other)?;
+        self.frames += 1;
+
+        Ok(())
+    }
+}
+
+/// Writes each frame to a directory as `frame_0000.ppm`, `frame_0001.ppm`, ...
+pub struct ImageWriter {
+    dir: PathBuf,
+    format: ImageFormat,
+    scale: usize,
+    pub paths: Vec<PathBuf>,
+}
+
+impl ImageWriter {
+    pub fn new(dir: &Path, format: ImageFormat, scale: usize) -> io::Result<Self> {
+        fs::create_dir_all(dir)?;
+
+        Ok(ImageWriter {
+            dir: dir.to_path_buf(),
+            format,
+            scale: scale.max(1),
+            paths: Vec::new(),
+        })
+    }
+}
+
+impl FrameWriter for ImageWriter {
+    fn write_frame(&mut self, frame: Frame) -> io::Result<()> {
+        let (w, h) = (frame.width * self.scale, frame.height * self.scale);
+        let ext = match self.format {
+            ImageFormat::Ppm => "ppm",
+            ImageFormat::Png => "png",
+        };
+        let path = self
+            .dir
+            .join(format!("frame_{:04}.{}", self.paths.len(), ext));
+        let mut out = BufWriter::new(File::create(&path)?);
+
+        let rgb = indexed_pixels(&frame, self.scale)
+            .into_iter()
+            .flat_map(|i| PALETTE[i as usize])
+            .collect::<Vec<u8>>();
+
+        match self.format {
+            ImageFormat::Ppm => {
+                write!(out, "P6\n{} {}\n255\n", w, h)?;
+                out.write_all(&rgb)?;
+            }
+            ImageFormat::Png => {
+                let mut encoder = png::Encoder::new(&mut out, w as u32, h as u32);
+                encoder.set_color(png::ColorType::Rgb);
+                encoder.set_depth(png::BitDepth::Eight);
+                encoder
+                    .write_header()
+                    .and_then(|mut writer| writer.write_image_data(&rgb))
+                    .map_err(io::Error::other)?;
+            }
+        }
+        out.flush()?;
+
+        self.paths.push(path);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, process};
+
+    use itertools::Itertools;
+
+    use super::*;
+    use crate::cave::{parse_paths, Grid, Slopes};
+    use crate::sim::{Algorithm, Floor, SandSim};
+
+    const TEST_INPUT: &str = "498,4 -> 498,6 -> 496,6
+503,4 -> 502,4 -> 502,9 -> 494,9";
+
+    fn record(floor: Floor, every: usize) -> Vec<Frame> {
+        let input = TEST_INPUT.split('\n').map(String::from).collect_vec();
+        let grid = Grid::from_paths(&parse_paths(&input, Slopes::Straight).unwrap());
+
+        let mut recorder = Recorder::new(every, Vec::new());
+        SandSim::new(grid, Pos::new(500, 0), floor)
+            .fill_observed(Algorithm::GrainByGrain, &mut recorder);
+
+        recorder.into_writer().unwrap()
+    }
+
+    #[test]
+    fn test_recorder() {
+        let frames = record(Floor::Abyss, 5);
+
+        assert_eq!(
+            frames.iter().map(|f| f.grains).collect_vec(),
+            vec![0, 5, 10, 15, 20, 24]
+        );
+        assert_eq!(
+            frames[0].to_string(),
+            "......+...
+..........
+..........
+..........
+....#...##
+....#...#.
+..###...#.
+........#.
+........#.
+#########.
+"
+        );
+        assert_eq!(
+            frames[5].to_string(),
+            "......+...
+..........
+......o...
+.....ooo..
+....#ooo##
+...o#ooo#.
+..###ooo#.
+....oooo#.
+.o.ooooo#.
+#########.
+"
+        );
+    }
+
+    #[test]
+    fn test_floor_is_drawn() {
+        let frames = record(Floor::BelowLowestRock(2), 1000);
+        let last = frames.last().unwrap();
+
+        assert_eq!(last.grains, 93);
+        assert_eq!(
+            last.to_string(),
+            "..........o..........
+.........ooo.........
+........ooooo........
+.......ooooooo.......
+......oo#ooo##o......
+.....ooo#ooo#ooo.....
+....oo###ooo#oooo....
+...oooo.oooo#ooooo...
+..oooooooooo#oooooo..
+.ooo#########ooooooo.
+ooooo.......ooooooooo
+#####################
+"
+        );
+    }
+
+    struct Closed;
+
+    impl Write for Closed {
+        fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+            Err(io::ErrorKind::BrokenPipe.into())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_animation_error() {
+        let mut animation = TerminalAnimation::new(Closed, Duration::ZERO);
+        let frame = record(Floor::Abyss, 1000).remove(0);
+
+        assert!(animation.wants_frame(0));
+        animation.push(frame);
+        assert!(!animation.wants_frame(1));
+        assert!(!animation.wants_final_frame());
+        assert_eq!(
+            animation.finish().map_err(|e| e.kind()),
+            Err(io::ErrorKind::BrokenPipe)
+        );
+    }
+
+    #[test]
+    fn test_exports() {
+        let frames = record(Floor::Abyss, 10);
+
+        let mut gif = Vec::new();
+        let mut writer = GifWriter::new(&mut gif, 2, Duration::from_millis(100));
+        for frame in frames.clone() {
+            writer.write_frame(frame).unwrap();
+        }
+        assert_eq!(writer.finish().unwrap(), 4);
+        assert_eq!(&gif[..6], b"GIF89a");
+        assert_eq!(gif.last(), Some(&0x3b));
+
+        let dir = env::temp_dir().join(format!("day14_frames_test_{}", process::id()));
+        let mut writer = ImageWriter::new(&dir, ImageFormat::Ppm, 1).unwrap();
+        for frame in frames {
+            writer.write_frame(frame).unwrap();
+        }
+        let paths = writer.paths;
+        assert_eq!(paths.len(), 4);
+        assert!(paths[3].ends_with("frame_0003.ppm"));
+
+        let ppm = fs::read(&paths[0]).unwrap();
+        assert!(ppm.starts_with(b"P6\n10 10\n255\n"));
+        assert_eq!(ppm.len(), 13 + 10 * 10 * 3);
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
@@ -1,10 +1,22 @@
+use std::env;
 use std::fmt::Display;
-use std::time::Instant;
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
 
+use aoc_common::cli::options;
 use aoc_common::get_input;
 
-mod part1;
-mod part2;
+use crate::cave::{parse_paths, Grid, Pos, Slopes};
+use crate::frames::{
+    GifWriter, ImageFormat, ImageWriter, Recorder, TerminalAnimation, MAX_FRAME_CELLS,
+};
+use crate::sim::{Algorithm, Floor, SandSim};
+
+mod cave;
+mod frames;
+mod sim;
 
 fn main() {
     let input = get_input("day14.txt");
@@ -18,79 +30,200 @@ fn main() {
     println!("Part 1: {}", r1);
     println!("Part 2: {}", r2);
     println!("Duration: {:.3}ms", t);
-}
 
-fn solve(input: &[String]) -> (impl Display, impl Display) {
-    let p1 = part1::solve(input);
-    let p2 = part2::solve(input);
-
-    assert_eq!(p1, 1513);
-    assert_eq!(p2, 22646);
+    let args = env::args().skip(1).collect::<Vec<_>>();
+    if !args.is_empty() {
+        let opts = Options::from_args(&args).unwrap_or_else(|e| panic!("{}", e));
+        run(&input, &opts).unwrap_or_else(|e| panic!("{}", e));
+    }
+}
 
-    (p1, p2)
+enum Output {
+    Count,
+    Animate(Duration),
+    Gif(PathBuf),
+    Images(PathBuf, ImageFormat),
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-enum Element {
-    Air,
-    Sand,
-    Rock,
-    Void,
+struct Options {
+    source: Pos,
+    floor: Floor,
+    slopes: Slopes,
+    algorithm: Algorithm,
+    output: Output,
+    every: usize,
+    scale: usize,
 }
 
-struct Grid {
-    elements: Vec<Vec<Element>>,
-    width: usize,
-    height: usize,
-    offset_x: usize,
+impl Options {
+    /// Parses `[--source X,Y] [--floor abyss|+DY|Y] [--slopes straight|diagonal|any]
+    /// [--algorithm drop|resume|flood] [--animate FPS | --gif FILE | --ppm DIR | --png DIR]
+    /// [--every N] [--scale N]`, where `+DY` is relative to the lowest rock and exports keep a
+    /// frame every 50 grains by default
+    fn from_args(args: &[String]) -> Result<Self, String> {
+        let mut opts = Options {
+            source: SOURCE,
+            floor: Floor::Abyss,
+            slopes: Slopes::Diagonal,
+            algorithm: Algorithm::GrainByGrain,
+            output: Output::Count,
+            every: 50,
+            scale: 4,
+        };
+
+        for opt in options(args) {
+            let opt = opt?;
+
+            match opt.name {
+                "--source" => {
+                    let (x, y) = opt.value.split_once(',').ok_or_else(|| opt.invalid())?;
+                    opts.source = Pos::new(
+                        x.parse().map_err(|_| opt.invalid())?,
+                        y.parse().map_err(|_| opt.invalid())?,
+                    );
+                    if opts.source.y < 0 {
+                        return Err(format!("Negative depth for {}: {}", opt.name, opt.value));
+                    }
+                }
+                "--floor" => {
+                    opts.floor = match opt.value {
+                        "abyss" => Floor::Abyss,
+                        v => match v.strip_prefix('+') {
+                            Some(dy) => {
+                                Floor::BelowLowestRock(dy.parse().map_err(|_| opt.invalid())?)
+                            }
+                            None => Floor::At(v.parse().map_err(|_| opt.invalid())?),
+                        },
+                    };
+                    if matches!(opts.floor, Floor::At(y) if y < 0) {
+                        return Err(format!("Negative depth for {}: {}", opt.name, opt.value));
+                    }
+                }
+                "--slopes" => {
+                    opts.slopes = match opt.value {
+                        "straight" => Slopes::Straight,
+                        "diagonal" => Slopes::Diagonal,
+                        "any" => Slopes::Any,
+                        _ => return Err(opt.invalid()),
+                    };
+                }
+                "--algorithm" => {
+                    opts.algorithm = match opt.value {
+                        "drop" => Algorithm::GrainByGrain,
+                        "resume" => Algorithm::ResumePath,
+                        "flood" => Algorithm::FloodFill,
+                        _ => return Err(opt.invalid()),
+                    };
+                }
+                "--animate" => {
+                    let fps: f64 = opt.parse()?;
+                    if fps <= 0.0 {
+                        return Err(opt.invalid());
+                    }
+                    opts.output = Output::Animate(Duration::from_secs_f64(1.0 / fps));
+                }
+                "--gif" => opts.output = Output::Gif(PathBuf::from(opt.value)),
+                "--ppm" => opts.output = Output::Images(PathBuf::from(opt.value), ImageFormat::Ppm),
+                "--png" => opts.output = Output::Images(PathBuf::from(opt.value), ImageFormat::Png),
+                "--every" => opts.every = opt.parse()?,
+                "--scale" => opts.scale = opt.parse()?,
+                _ => return Err(opt.unknown()),
+            }
+        }
+
+        Ok(opts)
+    }
 }
 
-impl Grid {
-    fn new(w: usize, h: usize, offset_x: usize) -> Self {
-        let mut elements = Vec::with_capacity(h);
+fn run(input: &[String], opts: &Options) -> io::Result<()> {
+    let paths = parse_paths(input, opts.slopes).map_err(io::Error::other)?;
+    let grid = Grid::from_paths(&paths);
+    let mut sim = SandSim::new(grid, opts.source, opts.floor);
+
+    if !matches!(opts.output, Output::Count) && sim.frame_cells() > MAX_FRAME_CELLS {
+        return Err(io::Error::other(format!(
+            "Frames of {} cells are too large to capture, the limit is {}",
+            sim.frame_cells(),
+            MAX_FRAME_CELLS
+        )));
+    }
+
+    let grains = match &opts.output {
+        Output::Count => sim.fill_with(opts.algorithm),
+        Output::Animate(interval) => {
+            let mut animation = TerminalAnimation::new(io::stdout().lock(), *interval);
+            let grains = sim.fill_observed(opts.algorithm, &mut animation);
+            animation.finish()?;
 
-        for _ in 0..h {
-            elements.push(vec![Element::Air; w]);
+            grains
         }
+        Output::Gif(path) => {
+            let out = BufWriter::new(File::create(path)?);
+            let gif = GifWriter::new(out, opts.scale, Duration::from_millis(50));
+            let mut recorder = Recorder::new(opts.every, gif);
+            let grains = sim.fill_observed(opts.algorithm, &mut recorder);
+
+            let frames = recorder.into_writer()?.finish()?;
+            println!("Wrote {} frames to {}", frames, path.display());
 
-        Grid {
-            elements,
-            width: w,
-            height: h,
-            offset_x,
+            grains
         }
-    }
+        Output::Images(dir, format) => {
+            let images = ImageWriter::new(dir, *format, opts.scale)?;
+            let mut recorder = Recorder::new(opts.every, images);
+            let grains = sim.fill_observed(opts.algorithm, &mut recorder);
+
+            let paths = recorder.into_writer()?.paths;
+            println!("Wrote {} frames to {}", paths.len(), dir.display());
 
-    fn get(&self, x: usize, y: usize) -> Element {
-        if x < self.offset_x || x - self.offset_x >= self.width || y >= self.height {
-            return Element::Void;
+            grains
         }
+    };
 
-        self.elements[y][x - self.offset_x].clone()
-    }
+    println!(
+        "Sand from {},{} with {:?}: {}",
+        opts.source.x, opts.source.y, opts.floor, grains
+    );
 
-    fn set(&mut self, x: usize, y: usize, element: Element) {
-        self.elements[y][x - self.offset_x] = element;
-    }
+    Ok(())
+}
 
-    #[cfg(test)]
-    fn render(&self) {
-        for row in &self.elements {
-            let r = row
-                .iter()
-                .map(|e| match e {
-                    Element::Air => '.',
-                    Element::Sand => 'o',
-                    Element::Rock => '#',
-                    Element::Void => ' ',
-                })
-                .collect::<String>();
-
-            println!("{}", r);
-        }
-        println!();
+fn solve(input: &[String]) -> (impl Display, impl Display) {
+    let paths = parse_paths(input, Slopes::Straight).unwrap_or_else(|e| panic!("{}", e));
+    let grid = Grid::from_paths(&paths);
+
+    let p1 = SandSim::new(grid.clone(), SOURCE, Floor::Abyss).fill_with(Algorithm::ResumePath);
+    let p2 = SandSim::new(grid, SOURCE, Floor::BelowLowestRock(2)).fill_with(Algorithm::FloodFill);
+
+    assert_eq!(p1, 1513);
+    assert_eq!(p2, 22646);
+
+    (p1, p2)
+}
+
+const SOURCE: Pos = Pos { x: 500, y: 0 };
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(s: &str) -> Vec<String> {
+        s.split_whitespace().map(String::from).collect()
     }
 
-    #[cfg(not(test))]
-    fn render(&self) {}
+    #[test]
+    fn test_negative_depths() {
+        assert_eq!(
+            Options::from_args(&args("--source 500,-5 --floor -3")).err(),
+            Some(String::from("Negative depth for --source: 500,-5"))
+        );
+        assert_eq!(
+            Options::from_args(&args("--floor -3")).err(),
+            Some(String::from("Negative depth for --floor: -3"))
+        );
+
+        let opts = Options::from_args(&args("--source 500,0 --floor +2")).unwrap();
+        assert_eq!(opts.source, Pos::new(500, 0));
+        assert_eq!(opts.floor, Floor::BelowLowestRock(2));
+    }
 }
@@ -0,0 +1,369 @@
+use crate::cave::{Element, Grid, Pos};
+use crate::frames::{Frame, FrameSink, NoFrames};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Floor {
+    /// No floor: sand falling below the lowest rock is lost
+    Abyss,
+    /// An infinite floor this many rows below the lowest rock. The puzzle uses 2.
+    BelowLowestRock(i64),
+    /// An infinite floor at a fixed depth
+    At(i64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Algorithm {
+    /// Drops every grain from the source, one cell at a time
+    GrainByGrain,
+    /// Starts each grain where the previous one was before it came to rest, since everything
+    /// above that point is unchanged
+    ResumePath,
+    /// Computes the cells reachable from the source row by row. Only applies with a floor, where
+    /// every reachable cell ends up filled; falls back to `ResumePath` with `Floor::Abyss`.
+    FloodFill,
+}
+
+pub struct SandSim {
+    grid: Grid,
+    source: Pos,
+    floor_y: Option<i64>,
+    abyss_y: i64,
+    grains: usize,
+    path: Vec<Pos>,
+    /// Area covered by frames, see `frame`
+    frame_x: (i64, i64),
+    frame_height: usize,
+}
+
+impl SandSim {
+    pub fn new(grid: Grid, source: Pos, floor: Floor) -> Self {
+        let lowest = grid.max_rock_y().unwrap_or(source.y);
+
+        let floor_y = match floor {
+            Floor::Abyss => None,
+            Floor::BelowLowestRock(dy) => Some(lowest + dy),
+            Floor::At(y) => Some(y),
+        };
+
+        // Sand only comes to rest between rocks, or above the floor within the slopes going down
+        // from the source, which it can't move away from faster than it falls
+        let x_range = grid.x_range();
+        let (mut min_x, mut max_x) = if x_range.is_empty() {
+            (source.x, source.x + 1)
+        } else {
+            (x_range.start.min(source.x), x_range.end.max(source.x + 1))
+        };
+        if let Some(y) = floor_y {
+            let spread = y.saturating_sub(1 + source.y).max(0);
+            min_x = min_x.min(source.x.saturating_sub(spread));
+            max_x = max_x.max(source.x.saturating_add(spread + 1));
+        }
+        let frame_height = (grid.height() as i64)
+            .max(source.y + 1)
+            .max(floor_y.map_or(0, |y| y.saturating_add(1))) as usize;
+
+        SandSim {
+            grid,
+            source,
+            floor_y,
+            abyss_y: lowest,
+            grains: 0,
+            path: Vec::new(),
+            frame_x: (min_x, max_x),
+            frame_height,
+        }
+    }
+
+    /// Snapshot of the cave, including the source and the floor. Frames cover every cell sand can
+    /// come to rest in from the start, so they all have the same size.
+    pub fn frame(&self) -> Frame {
+        let (min_x, max_x) = self.frame_x;
+        let height = self.frame_height;
+
+        let cells = (0..height as i64)
+            .flat_map(|y| (min_x..max_x).map(move |x| (x, y)))
+            .map(|(x, y)| {
+                if Some(y) == self.floor_y {
+                    Element::Rock
+                } else {
+                    self.grid.get(x, y)
+                }
+            })
+            .collect();
+
+        Frame {
+            min_x,
+            width: (max_x - min_x) as usize,
+            height,
+            cells,
+            source: self.source,
+            grains: self.grains,
+        }
+    }
+
+    /// Number of cells in every frame
+    pub fn frame_cells(&self) -> u128 {
+        self.frame_x.1.abs_diff(self.frame_x.0) as u128 * self.frame_height as u128
+    }
+
+    fn capture<S: FrameSink>(&self, sink: &mut S) {
+        if sink.wants_frame(self.grains) {
+            sink.push(self.frame());
+        }
+    }
+
+    /// Whether sand can move into the cell. The floor is infinite, so nothing below it is free
+    /// either, which also blocks the source when the floor is at or above it.
+    fn is_free(&self, x: i64, y: i64) -> bool {
+        self.floor_y.is_none_or(|f| y < f) && self.grid.get(x, y) == Element::Air
+    }
+
+    /// Drops a single grain of sand from the source, returning where it came to rest, or `None`
+    /// if it fell into the abyss or the source is blocked
+    pub fn drop_grain(&mut self) -> Option<Pos> {
+        let Pos { mut x, mut y } = self.source;
+
+        if !self.is_free(x, y) {
+            return None;
+        }
+
+        'fall: loop {
+            if self.floor_y.is_none() && y >= self.abyss_y {
+                return None;
+            }
+
+            for dx in [0, -1, 1] {
+                if self.is_free(x + dx, y + 1) {
+                    x += dx;
+                    y += 1;
+                    continue 'fall;
+                }
+            }
+
+            break;
+        }
+
+        self.grid.set(x, y, Element::Sand);
+        self.grains += 1;
+
+        Some(Pos::new(x, y))
+    }
+
+    /// Same as `drop_grain`, but resumes from the path of the previous grain
+    fn drop_grain_from_path(&mut self) -> Option<Pos> {
+        // Cells only ever get filled, so the path is still valid up to its first filled cell
+        if let Some(i) = self.path.iter().position(|p| !self.is_free(p.x, p.y)) {
+            self.path.truncate(i);
+        }
+
+        if self.path.is_empty() {
+            if !self.is_free(self.source.x, self.source.y) {
+                return None;
+            }
+            self.path.push(self.source);
+        }
+
+        'fall: loop {
+            let Pos { x, y } = *self.path.last().unwrap();
+
+            if self.floor_y.is_none() && y >= self.abyss_y {
+                return None;
+            }
+
+            for dx in [0, -1, 1] {
+                if self.is_free(x + dx, y + 1) {
+                    self.path.push(Pos::new(x + dx, y + 1));
+                    continue 'fall;
+                }
+            }
+
+            break;
+        }
+
+        let p = self.path.pop().unwrap();
+        self.grid.set(p.x, p.y, Element::Sand);
+        self.grains += 1;
+
+        Some(p)
+    }
+
+    fn flood_fill<S: FrameSink>(&mut self, floor_y: i64, sink: &mut S) {
+        let Pos { x, y } = self.source;
+        if y >= floor_y || !self.is_free(x, y) {
+            return;
+        }
+
+        let mut row = vec![x];
+        self.grid.set(x, y, Element::Sand);
+        self.grains += 1;
+
+        for y in (y + 1)..floor_y {
+            let (min_x, max_x) = (row[0] - 1, row[row.len() - 1] + 1);
+            let mut next = Vec::with_capacity(row.len() + 2);
+            let mut above = row.iter().peekable();
+
+            for x in min_x..=max_x {
+                // Skip the cells of the row above that can't reach x anymore
+                while above.next_if(|&&ax| ax < x - 1).is_some() {}
+
+                let reachable = above.peek().is_some_and(|&&ax| ax <= x + 1);
+                if reachable && self.is_free(x, y) {
+                    next.push(x);
+                }
+            }
+
+            if next.is_empty() {
+                break;
+            }
+
+            for &x in &next {
+                self.grid.set(x, y, Element::Sand);
+            }
+            self.grains += next.len();
+            row = next;
+
+            self.capture(sink);
+        }
+    }
+
+    /// Drops sand until it either falls into the abyss or blocks the source, returning the number
+    /// of grains at rest
+    pub fn fill_with(&mut self, algorithm: Algorithm) -> usize {
+        self.fill_observed(algorithm, &mut NoFrames)
+    }
+
+    /// Same as `fill_with`, offering a frame to `sink` every time sand comes to rest. The final
+    /// state is always captured.
+    pub fn fill_observed<S: FrameSink>(&mut self, algorithm: Algorithm, sink: &mut S) -> usize {
+        self.capture(sink);
+
+        match (algorithm, self.floor_y) {
+            (Algorithm::GrainByGrain, _) => {
+                while self.drop_grain().is_some() {
+                    self.capture(sink);
+                }
+            }
+            (Algorithm::FloodFill, Some(floor_y)) => self.flood_fill(floor_y, sink),
+            (Algorithm::ResumePath, _) | (Algorithm::FloodFill, None) => {
+                while self.drop_grain_from_path().is_some() {
+                    self.capture(sink);
+                }
+            }
+        }
+
+        if sink.wants_final_frame() {
+            sink.push(self.frame());
+        }
+
+        self.grains
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use super::*;
+    use crate::cave::{parse_paths, Slopes};
+
+    const TEST_INPUT: &str = "498,4 -> 498,6 -> 496,6
+503,4 -> 502,4 -> 502,9 -> 494,9";
+
+    fn grid() -> Grid {
+        let input = TEST_INPUT.split('\n').map(String::from).collect_vec();
+
+        Grid::from_paths(&parse_paths(&input, Slopes::Straight).unwrap())
+    }
+
+    #[test]
+    fn test_abyss() {
+        let mut sim = SandSim::new(grid(), Pos::new(500, 0), Floor::Abyss);
+
+        assert_eq!(sim.drop_grain(), Some(Pos::new(500, 8)));
+        assert_eq!(sim.drop_grain(), Some(Pos::new(499, 8)));
+        assert_eq!(sim.fill_with(Algorithm::GrainByGrain), 24);
+    }
+
+    #[test]
+    fn test_floor() {
+        let mut sim = SandSim::new(grid(), Pos::new(500, 0), Floor::BelowLowestRock(2));
+        assert_eq!(sim.frame_cells(), 21 * 12);
+        assert_eq!(sim.fill_with(Algorithm::GrainByGrain), 93);
+
+        let mut sim = SandSim::new(grid(), Pos::new(500, 0), Floor::At(11));
+        assert_eq!(sim.fill_with(Algorithm::GrainByGrain), 93);
+    }
+
+    #[test]
+    fn test_custom_floor_and_source() {
+        // Without rocks, sand piles up in a triangle on the floor
+        let mut sim = SandSim::new(Grid::default(), Pos::new(-10, 5), Floor::At(8));
+        assert_eq!(sim.drop_grain(), Some(Pos::new(-10, 7)));
+        assert_eq!(sim.drop_grain(), Some(Pos::new(-11, 7)));
+        assert_eq!(sim.drop_grain(), Some(Pos::new(-9, 7)));
+        assert_eq!(sim.fill_with(Algorithm::GrainByGrain), 1 + 3 + 5);
+
+        let mut sim = SandSim::new(grid(), Pos::new(497, 0), Floor::Abyss);
+        assert_eq!(sim.drop_grain(), Some(Pos::new(497, 5)));
+    }
+
+    #[test]
+    fn test_deep_floor_frame() {
+        let sim = SandSim::new(grid(), Pos::new(500, 0), Floor::At(5_000_000));
+        assert_eq!(sim.frame_cells(), (2 * 4_999_999 + 1) * 5_000_001);
+
+        let sim = SandSim::new(grid(), Pos::new(500, 0), Floor::At(i64::MAX));
+        assert!(sim.frame_cells() > u64::MAX as u128);
+    }
+
+    #[test]
+    fn test_algorithms_agree() {
+        let grids = [
+            grid(),
+            Grid::default(),
+            Grid::from_paths(&[
+                vec![Pos::new(490, 5), Pos::new(510, 5)],
+                vec![Pos::new(495, 8), Pos::new(495, 12), Pos::new(505, 12)],
+                vec![Pos::new(500, 2)],
+            ]),
+        ];
+        let floors = [
+            Floor::Abyss,
+            Floor::BelowLowestRock(2),
+            Floor::BelowLowestRock(5),
+            Floor::BelowLowestRock(-20),
+            Floor::At(1),
+            Floor::At(0),
+            Floor::At(-1),
+        ];
+
+        for grid in &grids {
+            for floor in floors {
+                let expected = SandSim::new(grid.clone(), Pos::new(500, 0), floor)
+                    .fill_with(Algorithm::GrainByGrain);
+
+                for algorithm in [Algorithm::ResumePath, Algorithm::FloodFill] {
+                    let mut sim = SandSim::new(grid.clone(), Pos::new(500, 0), floor);
+                    assert_eq!(
+                        sim.fill_with(algorithm),
+                        expected,
+                        "{:?} with {:?}",
+                        algorithm,
+                        floor
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_resume_path_after_single_drops() {
+        let mut sim = SandSim::new(grid(), Pos::new(500, 0), Floor::BelowLowestRock(2));
+
+        sim.drop_grain_from_path();
+        sim.drop_grain();
+        sim.drop_grain();
+        assert_eq!(sim.drop_grain_from_path(), Some(Pos::new(500, 7)));
+        assert_eq!(sim.fill_with(Algorithm::ResumePath), 93);
+    }
+}
@@ -128,8 +128,8 @@ fn get_equation(id: &str, monkeys: &HashMap<String, Monkey>) -> String {
             let na = va.parse::<u64>();
             let nb = vb.parse::<u64>();
 
-            if na.is_ok() && nb.is_ok() {
-                format!("{}", op(na.unwrap(), nb.unwrap()))
+            if let (Ok(na), Ok(nb)) = (na, nb) {
+                format!("{}", op(na, nb))
             } else {
                 format!("({} {} {})", va, s, vb)
             }